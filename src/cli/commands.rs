@@ -0,0 +1,311 @@
+use std::{collections::HashMap, env};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::tmux::{
+    panes::{Pane, PaneService},
+    sessions::{Session, SessionService},
+    target::Target,
+    template::Template,
+    tmux_command::WindowPos,
+    tree::ServerTree,
+    windows::{IdW, Window, WindowService},
+};
+
+use super::{Command, ListKind, TemplateCommand};
+
+/// Plain text representation of an entity, one line per entity
+trait Plain {
+    fn plain(&self) -> String;
+}
+
+impl Plain for Session {
+    fn plain(&self) -> String {
+        format!("{}\t{}", self.id, self.name)
+    }
+}
+
+impl Plain for Window {
+    fn plain(&self) -> String {
+        format!("{}\t{}", self.id, self.name)
+    }
+}
+
+impl Plain for Pane {
+    fn plain(&self) -> String {
+        format!("{}\t{}", self.id, self.label())
+    }
+}
+
+/// Outcome of sending a command to a window
+#[derive(Serialize)]
+struct Sent {
+    window: IdW,
+    name: String,
+    ok: bool,
+    error: Option<String>,
+}
+
+impl Plain for Sent {
+    fn plain(&self) -> String {
+        match &self.error {
+            None => format!("ok\t{}\t{}", self.window, self.name),
+            Some(error) => format!("failed\t{}\t{}\t{error}", self.window, self.name),
+        }
+    }
+}
+
+fn print<T: Serialize + Plain>(items: &[T], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(items)?);
+    } else {
+        items.iter().for_each(|item| println!("{}", item.plain()));
+    }
+    Ok(())
+}
+
+pub fn run(command: Command, json: bool) -> Result<()> {
+    use Command::*;
+
+    match command {
+        List { kind, target } => list(kind, target.map(resolve).transpose()?, json),
+        New { name, target } => create(&name, target.map(resolve).transpose()?, json),
+        Kill { target } => kill(&resolve(target)?),
+        Rename { target, new_name } => rename(&resolve(target)?, &new_name),
+        Attach { target } if env::var_os("TMUX").is_some() => switch(&resolve(target)?),
+        Attach { target } => SessionService::attach_terminal(&resolve(target)?),
+        Switch { target } => switch(&resolve(target)?),
+        Pin { target } => session(&target).and_then(SessionService::pin),
+        Unpin { target } => session(&target).and_then(SessionService::unpin),
+        Send { pattern, command } => send(&pattern, &command, json),
+        Dump => dump(),
+        Template { command } => template(command, json),
+    }
+}
+
+/// looks up the id of a window given by name, as tmux reads a '.' in the name
+/// of a `-t` target as the start of a pane index
+fn resolve(target: Target) -> Result<Target> {
+    match target {
+        Target::WindowName(..) => Ok(WindowService::get_window(&target)?.id.into()),
+        target => Ok(target),
+    }
+}
+
+fn sessions(target: Option<Target>) -> Result<Vec<Target>> {
+    match target {
+        Some(target) => Ok(vec![target]),
+        None => Ok(SessionService::get_all()?
+            .into_iter()
+            .map(|s| s.id.into())
+            .collect()),
+    }
+}
+
+fn list(kind: ListKind, target: Option<Target>, json: bool) -> Result<()> {
+    match kind {
+        ListKind::Sessions => print(&SessionService::get_all()?, json),
+        ListKind::Windows => {
+            let mut windows = vec![];
+            for session in sessions(target)? {
+                windows.extend(WindowService::get_all(&session)?);
+            }
+            print(&windows, json)
+        }
+        ListKind::Panes => {
+            let windows = match target {
+                Some(target @ (Target::Window(_) | Target::WindowName(..))) => vec![target],
+                target => {
+                    let mut windows = vec![];
+                    for session in sessions(target)? {
+                        let ids = WindowService::get_all(&session)?.into_iter();
+                        windows.extend(ids.map(|w| w.id.into()));
+                    }
+                    windows
+                }
+            };
+            let mut panes = vec![];
+            for window in windows {
+                panes.extend(PaneService::get_all(&window)?);
+            }
+            print(&panes, json)
+        }
+    }
+}
+
+fn create(name: &str, target: Option<Target>, json: bool) -> Result<()> {
+    match target {
+        None => {
+            SessionService::create(name, None)?;
+            let session = SessionService::get_session(&Target::SessionName(name.into()))?;
+            print(&[session], json)
+        }
+        Some(session) => {
+            WindowService::create(name, &session, &WindowPos::After, None)?;
+            let window = WindowService::get_last_created_window_id(&session)
+                .and_then(|id| WindowService::get_window(&id.into()))?;
+            print(&[window], json)
+        }
+    }
+}
+
+fn kill(target: &Target) -> Result<()> {
+    match target {
+        Target::Session(_) | Target::SessionName(_) => SessionService::kill(target),
+        Target::Window(_) | Target::WindowName(..) => WindowService::kill(target),
+        Target::Pane(_) => PaneService::kill(target),
+    }
+}
+
+fn rename(target: &Target, new_name: &str) -> Result<()> {
+    match target {
+        Target::Session(_) | Target::SessionName(_) => SessionService::rename(target, new_name),
+        Target::Window(_) | Target::WindowName(..) => WindowService::rename(target, new_name),
+        Target::Pane(_) => PaneService::rename(target, new_name),
+    }
+}
+
+fn switch(target: &Target) -> Result<()> {
+    match target {
+        Target::Session(_) | Target::SessionName(_) => SessionService::attach(target),
+        Target::Window(_) | Target::WindowName(..) => WindowService::attach(target),
+        Target::Pane(_) => PaneService::attach(target),
+    }
+}
+
+fn session(target: &Target) -> Result<&Target> {
+    match target {
+        Target::Session(_) | Target::SessionName(_) => Ok(target),
+        _ => Err(anyhow!("{target} is not a session")),
+    }
+}
+
+/// sends the command to the windows of the sessions matching the pattern, or
+/// to the matching windows when the pattern has a `session:window` form
+fn send(pattern: &str, command: &str, json: bool) -> Result<()> {
+    let sessions: HashMap<_, _> = SessionService::get_all()?
+        .into_iter()
+        .map(|s| (s.id, s.name))
+        .collect();
+
+    let mut windows = vec![];
+    for window in WindowService::get_server_windows()? {
+        let Some(session) = sessions.get(&window.session_id) else {
+            continue;
+        };
+        let name = format!("{session}:{}", window.name);
+        let matched = match pattern.contains(':') {
+            true => glob(pattern, &name),
+            false => glob(pattern, session),
+        };
+        if matched {
+            windows.push((window.id, name));
+        }
+    }
+    if windows.is_empty() {
+        return Err(anyhow!("no session or window matches {pattern}"));
+    }
+
+    let sent: Vec<Sent> = windows
+        .into_iter()
+        .map(
+            |(window, name)| match WindowService::send_keys(&window.into(), command) {
+                Ok(()) => Sent {
+                    window,
+                    name,
+                    ok: true,
+                    error: None,
+                },
+                Err(e) => Sent {
+                    window,
+                    name,
+                    ok: false,
+                    error: Some(format!("{e:#}")),
+                },
+            },
+        )
+        .collect();
+    print(&sent, json)?;
+
+    match sent.iter().filter(|s| !s.ok).count() {
+        0 => Ok(()),
+        failed => Err(anyhow!(
+            "sending failed for {failed} of {} windows",
+            sent.len()
+        )),
+    }
+}
+
+/// shell style matching where `*` is any run of characters and `?` any one
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // last `*` seen and the text position it currently stands for
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn dump() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&ServerTree::load()?)?);
+    Ok(())
+}
+
+fn template(command: TemplateCommand, json: bool) -> Result<()> {
+    match command {
+        TemplateCommand::List if json => {
+            println!("{}", serde_json::to_string_pretty(&Template::list()?)?);
+            Ok(())
+        }
+        TemplateCommand::List => {
+            Template::list()?.iter().for_each(|name| println!("{name}"));
+            Ok(())
+        }
+        TemplateCommand::Check { template } => Template::load(&template).map(|_| ()),
+        TemplateCommand::Create { template, name } => {
+            let session = Template::load(&template)?.build(name.as_deref())?;
+            print(&[session], json)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::glob;
+
+    #[test]
+    fn globs() {
+        assert!(glob("dev", "dev"));
+        assert!(!glob("dev", "devops"));
+        assert!(glob("dev*", "devops"));
+        assert!(glob("*-api", "billing-api"));
+        assert!(glob("web-?", "web-1"));
+        assert!(!glob("web-?", "web-10"));
+        assert!(glob("*:editor", "rusty:editor"));
+        assert!(glob("a*b*c", "axxbyybc"));
+        assert!(!glob("a*b*c", "axxbyyb"));
+        assert!(glob("*", ""));
+    }
+}
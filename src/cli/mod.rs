@@ -0,0 +1,92 @@
+pub mod commands;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::{tmux::target::Target, tui::sort::SortStrategy};
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Manage tmux sessions, windows and panes",
+    after_help = "Targets are given as $id, @id, %id, session or session:window"
+)]
+pub struct Cli {
+    /// print machine-readable JSON instead of plain text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// initial order of the sessions in the TUI
+    #[arg(long, value_enum)]
+    pub sort: Option<SortStrategy>,
+
+    /// runs the TUI when no command is given
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List sessions, windows or panes
+    List {
+        #[arg(value_enum, default_value_t)]
+        kind: ListKind,
+        /// only list the windows or panes of this target
+        #[arg(short, long)]
+        target: Option<Target>,
+    },
+    /// Create a session, or a window when a target session is given
+    New {
+        name: String,
+        /// session to create the window in
+        #[arg(short, long)]
+        target: Option<Target>,
+    },
+    /// Kill a session, window or pane
+    Kill { target: Target },
+    /// Rename a session or window, or set the title of a pane
+    Rename { target: Target, new_name: String },
+    /// Attach to a target, switching the client instead when inside tmux
+    Attach { target: Target },
+    /// Switch the current tmux client to a target
+    Switch { target: Target },
+    /// Pin a session to the top of the session list
+    Pin { target: Target },
+    /// Unpin a session
+    Unpin { target: Target },
+    /// Type a command into the active pane of every matching window
+    Send {
+        /// session name, or session:window, where `*` and `?` are wildcards
+        pattern: String,
+        /// command typed into each window, followed by enter
+        command: String,
+    },
+    /// Print every session, window and pane of the server as a JSON tree
+    Dump,
+    /// Manage session templates
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// List the templates found in the templates directory
+    List,
+    /// Check a template for errors without creating anything
+    Check { template: String },
+    /// Create a session from a template name or path
+    Create {
+        template: String,
+        /// name of the session, defaults to the template's session_name
+        name: Option<String>,
+    },
+}
+
+#[derive(ValueEnum, Default, Debug, Clone, Copy, PartialEq)]
+pub enum ListKind {
+    #[default]
+    Sessions,
+    Windows,
+    Panes,
+}
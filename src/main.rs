@@ -1,3 +1,4 @@
+mod cli;
 mod tmux;
 mod tui;
 
@@ -6,26 +7,58 @@ use std::{
     process::{exit, Command},
 };
 
+use clap::Parser;
+use cli::{commands, Cli};
 use ratatui::{backend::CrosstermBackend, Terminal};
+use tmux::sessions::SessionService;
 use tui::{
     app::App,
+    config::Config,
     event::EventHandler,
     tui::{Tui, TUI},
 };
 
 fn main() -> io::Result<()> {
-    if Command::new("tmux").arg("-V").status().is_err() {
+    let cli = Cli::parse();
+
+    if Command::new("tmux").arg("-V").output().is_err() {
         eprintln!("Couldn't run tmux");
         exit(1);
     }
 
+    if let Some(command) = cli.command {
+        if let Err(e) = commands::run(command, cli.json) {
+            eprintln!("{e:#}");
+            exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e:#}");
+            exit(1);
+        }
+    };
+
+    // the TUI needs a server to show, tmux tells why there's none
+    if let Err(e) = SessionService::get_all() {
+        eprintln!("{e:#}");
+        exit(1);
+    }
+
     let terminal: Tui = Terminal::new(CrosstermBackend::new(io::stdout()))?;
-    let events = EventHandler::new(250);
+    let events = EventHandler::new(config.tick_rate);
     let mut tui = TUI::new(terminal, events);
 
     tui.init()?;
 
-    let result = App::default().run(&mut tui);
+    let sort = cli.sort.unwrap_or(config.sort);
+    let result = App::default()
+        .with_config(config)
+        .with_sort(sort)
+        .run(&mut tui);
 
     tui.exit()?;
     result
@@ -0,0 +1,129 @@
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    thread,
+    time::Duration,
+};
+
+use super::{sessions::IdS, tmux_command::TmuxCommand, windows::IdW};
+
+/// Changes made to the server, as announced to control mode clients
+///
+/// Windows of other sessions than the one the client is attached to come as
+/// `%unlinked-window-*` notifications, both kinds are treated the same.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    SessionsChanged,
+    SessionRenamed(IdS, String),
+    WindowAdd(IdW),
+    WindowClose(IdW),
+    WindowRenamed(IdW, String),
+}
+
+impl Notification {
+    /// parses a line of control mode output, anything that isn't a known
+    /// notification is ignored
+    pub fn parse(line: &str) -> Option<Self> {
+        let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+        let name = name.strip_prefix('%')?;
+        let name = name.strip_prefix("unlinked-").unwrap_or(name);
+
+        let notification = match name {
+            "sessions-changed" => Self::SessionsChanged,
+            "session-renamed" => {
+                let (id, new_name) = args.split_once(' ')?;
+                Self::SessionRenamed(id.parse().ok()?, new_name.into())
+            }
+            "window-add" => Self::WindowAdd(args.parse().ok()?),
+            "window-close" => Self::WindowClose(args.parse().ok()?),
+            "window-renamed" => {
+                let (id, new_name) = args.split_once(' ')?;
+                Self::WindowRenamed(id.parse().ok()?, new_name.into())
+            }
+            _ => return None,
+        };
+        Some(notification)
+    }
+}
+
+/// Runs a control mode client in the background and hands its notifications
+/// to `notify` until it returns false
+///
+/// The client attaches to the session we run in and gets started again
+/// whenever tmux drops it. Outside of tmux nothing is started, attaching would
+/// mark a session attached that nobody looks at, so changes are only polled.
+pub fn listen<F>(notify: F)
+where
+    F: Fn(Notification) -> bool + Send + 'static,
+{
+    let Ok(pane) = env::var("TMUX_PANE") else {
+        return;
+    };
+
+    thread::spawn(move || loop {
+        if let Ok(mut child) = TmuxCommand::control_client(&pane).spawn() {
+            // tmux ends control mode once stdin closes, so it's kept open
+            let _stdin = child.stdin.take();
+            let lines = child
+                .stdout
+                .take()
+                .map(|stdout| BufReader::new(stdout).lines());
+
+            for line in lines.into_iter().flatten().map_while(Result::ok) {
+                let Some(notification) = Notification::parse(&line) else {
+                    continue;
+                };
+                if !notify(notification) {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return;
+                }
+            }
+            let _ = child.wait();
+        }
+        thread::sleep(Duration::from_secs(1));
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::Notification::{self, *};
+
+    #[test]
+    fn parses_notifications() {
+        assert_eq!(
+            Some(SessionsChanged),
+            Notification::parse("%sessions-changed")
+        );
+        assert_eq!(
+            Some(SessionRenamed(56.into(), "my session".into())),
+            Notification::parse("%session-renamed $56 my session")
+        );
+        assert_eq!(
+            Some(WindowAdd(70.into())),
+            Notification::parse("%window-add @70")
+        );
+        assert_eq!(
+            Some(WindowClose(72.into())),
+            Notification::parse("%unlinked-window-close @72")
+        );
+        assert_eq!(
+            Some(WindowRenamed(71.into(), "bar".into())),
+            Notification::parse("%unlinked-window-renamed @71 bar")
+        );
+    }
+
+    #[test]
+    fn ignores_everything_else() {
+        for line in [
+            "%begin 1792206220 2430 0",
+            "%session-changed $0 base",
+            "%window-add",
+            "%exit",
+            "sessions-changed",
+            "",
+        ] {
+            assert_eq!(None, Notification::parse(line), "{line:?}");
+        }
+    }
+}
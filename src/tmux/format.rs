@@ -0,0 +1,173 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+/// Separates fields in formatted tmux output
+///
+/// The ASCII unit separator can't be typed into a session or window name, unlike
+/// `,` or `:`, so names never need escaping.
+pub const SEPARATOR: char = '\x1f';
+
+/// Session user option marking sessions hidden from the TUI
+pub const HIDDEN_OPTION: &str = "@rts-hidden";
+
+/// Session user option holding the time a session got pinned at
+pub const PINNED_OPTION: &str = "@rts-pinned";
+
+/// Session user option holding the session's position in the manual order
+pub const ORDER_OPTION: &str = "@rts-order";
+
+pub const SESSION_FIELDS: &[&str] = &[
+    "session_id",
+    "session_name",
+    "session_attached",
+    "session_last_attached",
+    "session_windows",
+    "session_created",
+    HIDDEN_OPTION,
+    PINNED_OPTION,
+    ORDER_OPTION,
+];
+
+pub const WINDOW_FIELDS: &[&str] = &[
+    "window_id",
+    "session_id",
+    "window_name",
+    "window_active",
+    "window_activity",
+    "window_panes",
+];
+
+pub const PANE_FIELDS: &[&str] = &[
+    "pane_id",
+    "window_id",
+    "pane_index",
+    "pane_current_command",
+    "pane_current_path",
+    "pane_pid",
+    "pane_width",
+    "pane_height",
+    "pane_active",
+    "pane_title",
+];
+
+/// builds a `-F` format string printing the given fields
+pub fn format(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("#{{{field}}}"))
+        .collect::<Vec<_>>()
+        .join(&SEPARATOR.to_string())
+}
+
+/// One line of tmux output split into the fields it was formatted with
+#[derive(Debug)]
+pub struct Fields<'a> {
+    names: &'static [&'static str],
+    values: Vec<&'a str>,
+}
+
+impl<'a> Fields<'a> {
+    pub fn parse(line: &'a str, names: &'static [&'static str]) -> Result<Self> {
+        let values: Vec<_> = line.split(SEPARATOR).collect();
+
+        if values.len() != names.len() {
+            return Err(anyhow!(
+                "expected {} fields but found {} in {line:?}",
+                names.len(),
+                values.len()
+            ));
+        }
+        Ok(Self { names, values })
+    }
+
+    /// raw value of a field
+    pub fn get(&self, name: &str) -> Result<&'a str> {
+        self.names
+            .iter()
+            .position(|n| *n == name)
+            .map(|index| self.values[index])
+            .ok_or_else(|| anyhow!("unknown field {name}"))
+    }
+
+    pub fn parse_field<T>(&self, name: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.get(name)?;
+        value
+            .parse()
+            .map_err(|e| anyhow!("{e}"))
+            .with_context(|| format!("error parsing {name} from {value:?}"))
+    }
+
+    /// parses a field that tmux leaves empty when it has no value
+    pub fn parse_optional<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.get(name)? {
+            "" => Ok(None),
+            _ => self.parse_field(name).map(Some),
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> Result<bool> {
+        Ok(self.parse_field::<usize>(name)? > 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format, Fields, SEPARATOR};
+
+    const FIELDS: &[&str] = &["id", "name", "count"];
+
+    fn line(values: &[&str]) -> String {
+        values.join(&SEPARATOR.to_string())
+    }
+
+    #[test]
+    fn builds_format() {
+        assert_eq!("#{id}\x1f#{name}\x1f#{count}", format(FIELDS));
+    }
+
+    #[test]
+    fn keeps_commas_and_unicode() {
+        let line = line(&["1", "foo,bar: ünï ✓", "3"]);
+        let fields = Fields::parse(&line, FIELDS).unwrap();
+
+        assert_eq!("foo,bar: ünï ✓", fields.get("name").unwrap());
+        assert_eq!(3, fields.parse_field::<usize>("count").unwrap());
+    }
+
+    #[test]
+    fn empty_values() {
+        let line = line(&["1", "", ""]);
+        let fields = Fields::parse(&line, FIELDS).unwrap();
+
+        assert_eq!("", fields.get("name").unwrap());
+        assert_eq!(None, fields.parse_optional::<u64>("count").unwrap());
+        assert!(fields.parse_field::<u64>("count").is_err());
+    }
+
+    #[test]
+    fn missing_fields() {
+        let line = line(&["1", "name"]);
+        let error = Fields::parse(&line, FIELDS).unwrap_err();
+
+        assert!(error.to_string().contains("expected 3 fields but found 2"));
+    }
+
+    #[test]
+    fn invalid_values() {
+        let line = line(&["1", "name", "many"]);
+        let fields = Fields::parse(&line, FIELDS).unwrap();
+        let error = fields.parse_field::<usize>("count").unwrap_err();
+
+        assert!(error.to_string().contains("error parsing count"));
+        assert!(fields.get("unknown").is_err());
+    }
+}
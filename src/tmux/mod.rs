@@ -1,4 +1,10 @@
+pub mod control;
+pub mod format;
+pub mod panes;
 pub mod sessions;
+pub mod target;
+pub mod template;
 pub mod tmux;
 pub mod tmux_command;
+pub mod tree;
 pub mod windows;
@@ -0,0 +1,190 @@
+use core::str;
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Context, Error, Result};
+use serde::{Serialize, Serializer};
+
+use crate::tui::logger::Logger;
+
+use super::{
+    format::{Fields, PANE_FIELDS},
+    target::Target,
+    tmux::TmuxEntity,
+    tmux_command::{PaneResize, PaneSplit, TmuxCommand},
+    windows::IdW,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
+pub struct IdP(usize);
+
+impl From<usize> for IdP {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for IdP {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::prelude::v1::Result<Self, Self::Err> {
+        let num: usize = s.trim_start_matches('%').parse()?;
+        Ok(Self(num))
+    }
+}
+
+impl Display for IdP {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl Serialize for IdP {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Pane {
+    pub id: IdP,
+    pub window_id: IdW,
+    pub index: usize,
+    pub current_command: String,
+    pub current_path: String,
+    pub pid: u32,
+    pub width: u16,
+    pub height: u16,
+    pub is_active: bool,
+    pub title: String,
+}
+
+impl Pane {
+    /// label in the style of tmux's choose-tree: `index: command "title"`
+    pub fn label(&self) -> String {
+        format!(
+            "{}: {} \"{}\"",
+            self.index, self.current_command, self.title
+        )
+    }
+}
+
+impl TmuxEntity for Pane {}
+
+impl FromStr for Pane {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let fields = Fields::parse(s, PANE_FIELDS).context("error parsing pane")?;
+
+        Ok(Pane {
+            id: fields.parse_field("pane_id")?,
+            window_id: fields.parse_field("window_id")?,
+            index: fields.parse_field("pane_index")?,
+            current_command: fields.get("pane_current_command")?.into(),
+            current_path: fields.get("pane_current_path")?.into(),
+            pid: fields.parse_field("pane_pid")?,
+            width: fields.parse_field("pane_width")?,
+            height: fields.parse_field("pane_height")?,
+            is_active: fields.flag("pane_active")?,
+            title: fields.get("pane_title")?.into(),
+        })
+    }
+}
+
+pub struct PaneService;
+
+impl PaneService {
+    pub fn get_all(window: &Target) -> Result<Vec<Pane>> {
+        let panes = TmuxCommand::get_panes(window)?;
+
+        str::from_utf8(&panes)
+            .context("error parsing list-panes output")?
+            .lines()
+            .map(Pane::from_str)
+            .collect()
+    }
+
+    /// panes of every window on the server
+    pub fn get_server_panes() -> Result<Vec<Pane>> {
+        let panes = TmuxCommand::get_server_panes()?;
+
+        str::from_utf8(&panes)
+            .context("error parsing list-panes output")?
+            .lines()
+            .map(Pane::from_str)
+            .collect()
+    }
+
+    pub fn get_pane(target: &Target) -> Result<Pane> {
+        let pane = TmuxCommand::get_pane(target)?;
+
+        str::from_utf8(&pane)
+            .context("error parsing get-pane output")?
+            .lines()
+            .next()
+            .ok_or_else(|| anyhow!("can't find pane {target}"))
+            .and_then(Pane::from_str)
+    }
+
+    pub fn kill(target: &Target) -> Result<()> {
+        Logger::log(&format!("killing {target}"));
+        TmuxCommand::kill_pane(target)
+    }
+
+    /// splits the pane and returns the id of the newly created one
+    pub fn split(target: &Target, split: &PaneSplit, dir: Option<&Path>) -> Result<IdP> {
+        Logger::log(&format!("splitting {target}"));
+        let new_pane = TmuxCommand::split_pane(target, split, dir)?;
+
+        str::from_utf8(&new_pane)
+            .context("error parsing split-window output")?
+            .trim()
+            .parse()
+    }
+
+    pub fn rename(target: &Target, title: &str) -> Result<()> {
+        Logger::log(&format!("renaming {target}"));
+        TmuxCommand::rename_pane(target, title)
+    }
+
+    pub fn attach(target: &Target) -> Result<()> {
+        TmuxCommand::attach(target)
+    }
+
+    /// runs a command in the pane by typing it out
+    pub fn send_keys(target: &Target, command: &str) -> Result<()> {
+        TmuxCommand::send_keys(target, command)
+    }
+
+    /// what the pane currently shows, colours included as ANSI escapes
+    pub fn capture(target: &Target) -> Result<String> {
+        let content = TmuxCommand::capture_pane(target)?;
+        Ok(String::from_utf8_lossy(&content).into_owned())
+    }
+
+    pub fn select(target: &Target) -> Result<()> {
+        TmuxCommand::select_pane(target)
+    }
+
+    pub fn resize(target: &Target, resize: &PaneResize, amount: u16) -> Result<()> {
+        TmuxCommand::resize_pane(target, resize, amount)
+    }
+}
+
+#[test]
+fn from_str() {
+    let pane_str =
+        "%7\x1f@2\x1f1\x1fnvim\x1f/home/user/project\x1f4242\x1f120\x1f40\x1f1\x1feditor";
+    let pane = Pane::from_str(pane_str).unwrap();
+
+    assert_eq!(IdP::from(7), pane.id);
+    assert_eq!(IdW::from(2), pane.window_id);
+    assert_eq!(1, pane.index);
+    assert_eq!("nvim".to_string(), pane.current_command);
+    assert_eq!("/home/user/project".to_string(), pane.current_path);
+    assert_eq!(4242, pane.pid);
+    assert_eq!((120, 40), (pane.width, pane.height));
+    assert!(pane.is_active);
+    assert_eq!("editor".to_string(), pane.title);
+    assert_eq!("1: nvim \"editor\"".to_string(), pane.label());
+}
@@ -1,21 +1,63 @@
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
+    path::Path,
     str::{self, FromStr},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::{Context, Error, Result};
+use anyhow::{anyhow, Context, Error, Result};
+use serde::{Serialize, Serializer};
 
-use super::{tmux::TmuxEntity, tmux_command::TmuxCommand};
+use super::{
+    format::{Fields, HIDDEN_OPTION, ORDER_OPTION, PINNED_OPTION, SESSION_FIELDS},
+    target::Target,
+    tmux::TmuxEntity,
+    tmux_command::TmuxCommand,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
+pub struct IdS(usize);
+
+impl From<usize> for IdS {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for IdS {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::prelude::v1::Result<Self, Self::Err> {
+        let num: usize = s.trim_start_matches('$').parse()?;
+        Ok(Self(num))
+    }
+}
 
-#[derive(Debug, Clone, Default)]
+impl Display for IdS {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}", self.0)
+    }
+}
+
+impl Serialize for IdS {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Session {
-    pub id: usize,
+    pub id: IdS,
     pub name: String,
-    is_attached: bool,
+    /// whether a client other than a control mode one is attached, as our own
+    /// control client counts for tmux's `session_attached`
+    pub is_attached: bool,
     pub last_attached: Option<u64>,
-    created_at: u64,
-    windows_count: usize,
-    is_hidden: bool,
+    pub created_at: u64,
+    pub windows_count: usize,
+    pub is_hidden: bool,
+    pub pinned_at: Option<u64>,
+    pub order: Option<usize>,
 }
 
 impl Session {
@@ -31,26 +73,20 @@ impl FromStr for Session {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let parts: Vec<_> = s.split(',').collect();
-
-        assert_eq!(
-            parts.len(),
-            6,
-            "should be 6 parts in list-sessions format str"
-        );
+        let fields = Fields::parse(s, SESSION_FIELDS).context("error parsing session")?;
 
         let session = Session {
-            id: parts[0].trim_start_matches('$').parse().unwrap(),
-            name: parts[1].into(),
-            is_attached: parts[2] == "1",
-            last_attached: parts[3].parse::<u64>().ok(),
-            windows_count: parts[4]
-                .parse()
-                .context("error parsing session windows_count")?,
-            created_at: parts[5]
-                .parse()
-                .context("error parsing session created_at")?,
-            is_hidden: false,
+            id: fields.parse_field("session_id")?,
+            name: fields.get("session_name")?.into(),
+            is_attached: fields.flag("session_attached")?,
+            last_attached: fields.parse_optional("session_last_attached")?,
+            windows_count: fields.parse_field("session_windows")?,
+            created_at: fields.parse_field("session_created")?,
+            is_hidden: fields
+                .parse_optional::<usize>(HIDDEN_OPTION)?
+                .is_some_and(|hidden| hidden > 0),
+            pinned_at: fields.parse_optional(PINNED_OPTION)?,
+            order: fields.parse_optional(ORDER_OPTION)?,
         };
         Ok(session)
     }
@@ -62,44 +98,146 @@ impl SessionService {
     pub fn get_all() -> Result<Vec<Session>> {
         let sessions = TmuxCommand::get_sessions()?;
 
-        str::from_utf8(&sessions)
+        let sessions = str::from_utf8(&sessions)
             .context("error parsing list-sessions output")?
             .lines()
-            .map(|s| s.trim())
             .map(Session::from_str)
-            .collect()
+            .collect::<Result<_>>()?;
+        Self::with_attached(sessions)
     }
 
-    pub fn get_session(name: &str) -> Result<Session> {
-        let session = TmuxCommand::get_session(name)?;
+    pub fn get_session(target: &Target) -> Result<Session> {
+        let session = TmuxCommand::get_session(target)?;
 
-        str::from_utf8(&session)
-            .context("error parsing get session output")
-            .map(|s| s.trim())
-            .and_then(Session::from_str)
+        let session = str::from_utf8(&session)
+            .context("error parsing get session output")?
+            .lines()
+            .next()
+            .ok_or_else(|| anyhow!("can't find session {target}"))
+            .and_then(Session::from_str)?;
+        Ok(Self::with_attached(vec![session])?.remove(0))
     }
 
-    pub fn create(name: &str) -> Result<()> {
-        TmuxCommand::create_session(name)
+    fn with_attached(sessions: Vec<Session>) -> Result<Vec<Session>> {
+        let clients = TmuxCommand::get_client_sessions()?;
+        let clients = str::from_utf8(&clients).context("error parsing list-clients output")?;
+        Ok(mark_attached(sessions, clients))
     }
 
-    pub fn kill(name: &str) -> Result<()> {
-        TmuxCommand::kill_session(name)
+    pub fn create(name: &str, dir: Option<&Path>) -> Result<()> {
+        TmuxCommand::create_session(name, dir)
     }
 
-    pub fn rename(old_name: &str, new_name: &str) -> Result<()> {
-        TmuxCommand::rename_session(old_name, new_name)
+    pub fn kill(target: &Target) -> Result<()> {
+        TmuxCommand::kill_session(target)
     }
 
-    pub fn attach(name: &str) -> Result<()> {
-        TmuxCommand::attach_session(name)
+    pub fn rename(target: &Target, new_name: &str) -> Result<()> {
+        TmuxCommand::rename_session(target, new_name)
     }
 
-    pub fn hide(name: &str) -> Result<()> {
-        todo!()
+    pub fn attach(target: &Target) -> Result<()> {
+        TmuxCommand::attach(target)
     }
 
-    pub fn show(name: &str) -> Result<()> {
-        todo!()
+    /// attaches the current terminal, for when we're not running inside tmux
+    pub fn attach_terminal(target: &Target) -> Result<()> {
+        TmuxCommand::attach_session(target)
     }
+
+    /// names of the clients attached to the session
+    pub fn get_clients(target: &Target) -> Result<Vec<String>> {
+        let clients = TmuxCommand::get_clients(target)?;
+
+        Ok(str::from_utf8(&clients)
+            .context("error parsing list-clients output")?
+            .lines()
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    /// marks the session hidden, the option lives on the session so it
+    /// survives restarts of the app
+    pub fn hide(target: &Target) -> Result<()> {
+        TmuxCommand::set_session_option(target, HIDDEN_OPTION, "1")
+    }
+
+    pub fn show(target: &Target) -> Result<()> {
+        TmuxCommand::unset_session_option(target, HIDDEN_OPTION)
+    }
+
+    /// pins the session, pinned sessions are listed first in the order they
+    /// got pinned in
+    pub fn pin(target: &Target) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        TmuxCommand::set_session_option(target, PINNED_OPTION, &now.to_string())
+    }
+
+    pub fn unpin(target: &Target) -> Result<()> {
+        TmuxCommand::unset_session_option(target, PINNED_OPTION)
+    }
+
+    /// stores the manual order of the sessions, as their positions in `sessions`
+    pub fn reorder(sessions: &[IdS]) -> Result<()> {
+        let positions: Vec<(Target, String)> = sessions
+            .iter()
+            .enumerate()
+            .map(|(position, id)| ((*id).into(), position.to_string()))
+            .collect();
+        TmuxCommand::set_sessions_option(ORDER_OPTION, &positions)
+    }
+}
+
+/// marks the sessions listed in `clients`, the sessions of every client
+/// with control mode clients printed as empty lines
+fn mark_attached(mut sessions: Vec<Session>, clients: &str) -> Vec<Session> {
+    let attached: Vec<IdS> = clients.lines().filter_map(|id| id.parse().ok()).collect();
+    for session in sessions.iter_mut() {
+        session.is_attached = attached.contains(&session.id);
+    }
+    sessions
+}
+
+#[test]
+fn marks_attached_sessions() {
+    let sessions = vec![
+        Session::from_str("$1\x1fdev\x1f1\x1f\x1f1\x1f1722892534\x1f\x1f\x1f").unwrap(),
+        Session::from_str("$2\x1fops\x1f1\x1f\x1f1\x1f1722892534\x1f\x1f\x1f").unwrap(),
+    ];
+    assert!(sessions.iter().all(|s| s.is_attached));
+
+    // the second one only has our control client
+    let marked = mark_attached(sessions.clone(), "$1\n\n$1\n");
+    assert!(marked[0].is_attached);
+    assert!(!marked[1].is_attached);
+
+    let marked = mark_attached(sessions, "\n");
+    assert!(marked.iter().all(|s| !s.is_attached));
+}
+
+#[test]
+fn from_str() {
+    let session_str = "$3\x1ffoo,bar\x1f0\x1f\x1f2\x1f1722892534\x1f\x1f\x1f";
+    let session = Session::from_str(session_str).unwrap();
+
+    assert_eq!(IdS::from(3), session.id);
+    assert_eq!("foo,bar".to_string(), session.name);
+    assert!(!session.is_attached);
+    assert_eq!(None, session.last_attached);
+    assert_eq!(2, session.windows_count);
+    assert_eq!(1722892534, session.created_at);
+    assert!(!session.is_hidden);
+    assert_eq!(None, session.pinned_at);
+    assert_eq!(None, session.order);
+
+    let session_str = "$4\x1fbaz\x1f0\x1f\x1f1\x1f1722892534\x1f1\x1f1722892600\x1f2";
+    let session = Session::from_str(session_str).unwrap();
+    assert!(session.is_hidden);
+    assert_eq!(Some(1722892600), session.pinned_at);
+    assert_eq!(Some(2), session.order);
+    assert!(Session::from_str("$3\x1ffoo,bar").is_err());
 }
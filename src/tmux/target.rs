@@ -0,0 +1,144 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+
+use super::{panes::IdP, sessions::IdS, windows::IdW};
+
+/// Address of a tmux entity, either by its unique id or by its exact name
+///
+/// Displays as the value of a `-t` flag and can be turned into a `-f` filter
+/// that matches only the addressed entity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Session(IdS),
+    SessionName(String),
+    Window(IdW),
+    /// session name, window name
+    WindowName(String, String),
+    Pane(IdP),
+}
+
+impl Target {
+    /// format filter matching exactly the targeted entity
+    pub fn filter(&self) -> String {
+        match self {
+            Target::Session(id) => format!("#{{==:#{{session_id}},{id}}}"),
+            Target::SessionName(name) => {
+                format!("#{{==:#{{session_name}},{}}}", escape(name))
+            }
+            Target::Window(id) => format!("#{{==:#{{window_id}},{id}}}"),
+            Target::WindowName(session, window) => format!(
+                "#{{&&:#{{==:#{{session_name}},{}}},#{{==:#{{window_name}},{}}}}}",
+                escape(session),
+                escape(window)
+            ),
+            Target::Pane(id) => format!("#{{==:#{{pane_id}},{id}}}"),
+        }
+    }
+}
+
+/// escapes the characters that have a special meaning inside a tmux format
+fn escape(value: &str) -> String {
+    value
+        .replace('#', "##")
+        .replace(',', "#,")
+        .replace('}', "#}")
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Session(id) => write!(f, "{id}"),
+            // the trailing colon makes commands expecting a pane resolve a session
+            Target::SessionName(name) => write!(f, "={name}:"),
+            Target::Window(id) => write!(f, "{id}"),
+            Target::WindowName(session, window) => write!(f, "={session}:={window}"),
+            Target::Pane(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+/// parses `$1`, `@1` and `%1` as ids, `session:window` as a window name and
+/// anything else as a session name
+impl FromStr for Target {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let target = s.trim_start_matches('=').trim_end_matches(':');
+
+        if target.is_empty() {
+            return Err(anyhow!("empty target"));
+        }
+        let target = match target.chars().next() {
+            Some('$') => Target::Session(target.parse()?),
+            Some('@') => Target::Window(target.parse()?),
+            Some('%') => Target::Pane(target.parse()?),
+            _ => match target.split_once(':') {
+                Some((session, window)) => {
+                    Target::WindowName(session.into(), window.trim_start_matches('=').into())
+                }
+                None => Target::SessionName(target.into()),
+            },
+        };
+        Ok(target)
+    }
+}
+
+impl From<IdS> for Target {
+    fn from(value: IdS) -> Self {
+        Self::Session(value)
+    }
+}
+
+impl From<IdW> for Target {
+    fn from(value: IdW) -> Self {
+        Self::Window(value)
+    }
+}
+
+impl From<IdP> for Target {
+    fn from(value: IdP) -> Self {
+        Self::Pane(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Target;
+    use crate::tmux::{panes::IdP, sessions::IdS, windows::IdW};
+
+    #[test]
+    fn display() {
+        assert_eq!("$3", Target::from(IdS::from(3)).to_string());
+        assert_eq!("@4", Target::from(IdW::from(4)).to_string());
+        assert_eq!("%5", Target::from(IdP::from(5)).to_string());
+        assert_eq!("=dev:", Target::SessionName("dev".into()).to_string());
+        assert_eq!(
+            "=dev:=editor",
+            Target::WindowName("dev".into(), "editor".into()).to_string()
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Target::Session(IdS::from(1)), "$1".parse().unwrap());
+        assert_eq!(Target::Window(IdW::from(2)), "@2".parse().unwrap());
+        assert_eq!(Target::Pane(IdP::from(3)), "%3".parse().unwrap());
+        assert_eq!(Target::SessionName("dev".into()), "=dev".parse().unwrap());
+        assert_eq!(
+            Target::WindowName("dev".into(), "editor".into()),
+            "=dev:=editor".parse().unwrap()
+        );
+        assert!("$dev".parse::<Target>().is_err());
+        assert!("".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn filter_escapes_names() {
+        let target = Target::SessionName("a,b}c#d".into());
+        assert_eq!("#{==:#{session_name},a#,b#}c##d}", target.filter());
+
+        let target = Target::Window(IdW::from(1));
+        assert_eq!("#{==:#{window_id},@1}", target.filter());
+    }
+}
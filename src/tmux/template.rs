@@ -0,0 +1,471 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context, Error, Result};
+use serde::Deserialize;
+
+use super::{
+    panes::{IdP, PaneService},
+    sessions::{Session, SessionService},
+    target::Target,
+    tmux_command::{PaneSplit, WindowPos},
+    windows::{IdW, WindowService},
+};
+
+const LAYOUTS: &[&str] = &[
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-vertical",
+    "tiled",
+];
+
+/// Directory holding the app's configuration, `$XDG_CONFIG_HOME/rusty-tmux-sessions`
+pub fn config_dir() -> PathBuf {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .unwrap_or_default()
+        .join("rusty-tmux-sessions")
+}
+
+pub fn templates_dir() -> PathBuf {
+    config_dir().join("templates")
+}
+
+/// Description of a whole session, in the style of tmuxp's YAML configs
+///
+/// Relative directories are resolved against the parent's directory, so a
+/// window's `start_directory` is relative to the session's one.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Template {
+    pub session_name: String,
+    pub start_directory: Option<String>,
+    pub windows: Vec<WindowTemplate>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct WindowTemplate {
+    pub window_name: String,
+    pub start_directory: Option<String>,
+    pub layout: Option<String>,
+    #[serde(default)]
+    pub panes: Vec<PaneTemplate>,
+}
+
+/// A pane and the commands typed into it once created
+///
+/// Accepts tmuxp's shorthands: an empty entry, a single command, or a map whose
+/// `shell_command` is either a command or a list of them.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(from = "RawPane")]
+pub struct PaneTemplate {
+    pub shell_command: Vec<String>,
+    pub start_directory: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPane {
+    Empty,
+    Command(String),
+    Full {
+        #[serde(default)]
+        shell_command: Commands,
+        start_directory: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Default)]
+#[serde(untagged)]
+enum Commands {
+    #[default]
+    None,
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<RawPane> for PaneTemplate {
+    fn from(raw: RawPane) -> Self {
+        let (commands, start_directory) = match raw {
+            RawPane::Empty => (Commands::None, None),
+            RawPane::Command(command) => (Commands::One(command), None),
+            RawPane::Full {
+                shell_command,
+                start_directory,
+            } => (shell_command, start_directory),
+        };
+        let shell_command = match commands {
+            Commands::None => vec![],
+            Commands::One(command) => vec![command],
+            Commands::Many(commands) => commands,
+        };
+        Self {
+            shell_command,
+            start_directory,
+        }
+    }
+}
+
+/// expands `~` and resolves relative directories against `base`
+fn resolve(dir: Option<&str>, base: Option<&Path>) -> Option<PathBuf> {
+    let Some(dir) = dir else {
+        return base.map(Path::to_path_buf);
+    };
+    let dir = match dir.strip_prefix('~') {
+        Some(rest) => {
+            let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+            home.join(rest.trim_start_matches('/'))
+        }
+        None => PathBuf::from(dir),
+    };
+    match base {
+        Some(base) if dir.is_relative() => Some(base.join(dir)),
+        _ => Some(dir),
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let template: Template = serde_yaml::from_str(s).context("invalid template")?;
+        template.validate()?;
+        Ok(template)
+    }
+}
+
+impl Template {
+    /// loads a template by path, or by name from the templates directory
+    pub fn load(name: &str) -> Result<Self> {
+        let path = Path::new(name);
+        let path = match path.is_file() {
+            true => path.to_path_buf(),
+            false => ["yaml", "yml"]
+                .iter()
+                .map(|ext| templates_dir().join(format!("{name}.{ext}")))
+                .find(|path| path.is_file())
+                .ok_or_else(|| anyhow!("can't find template {name}"))?,
+        };
+
+        fs::read_to_string(&path)
+            .map_err(Error::from)
+            .and_then(|content| content.parse())
+            .with_context(|| format!("error loading template {}", path.display()))
+    }
+
+    /// names of the templates in the templates directory
+    pub fn list() -> Result<Vec<String>> {
+        let Ok(entries) = fs::read_dir(templates_dir()) else {
+            return Ok(vec![]);
+        };
+
+        let mut names = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            let is_yaml = path
+                .extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml");
+            if let (true, Some(name)) = (is_yaml, path.file_stem()) {
+                names.push(name.to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        check_session_name(&self.session_name)?;
+        if self.windows.is_empty() {
+            return Err(anyhow!("template should have at least one window"));
+        }
+        let session_dir = self.session_dir();
+        check_dir(session_dir.as_deref())?;
+
+        for window in &self.windows {
+            window
+                .validate(session_dir.as_deref())
+                .with_context(|| format!("invalid window {:?}", window.window_name))?;
+        }
+        Ok(())
+    }
+
+    fn session_dir(&self) -> Option<PathBuf> {
+        resolve(self.start_directory.as_deref(), None)
+    }
+
+    /// creates the session with all of its windows and panes, named `name` if
+    /// given or after the template otherwise
+    pub fn build(&self, name: Option<&str>) -> Result<Session> {
+        self.validate()?;
+        let name = name
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.session_name);
+        check_session_name(name)?;
+        let session_dir = self.session_dir();
+        let first = self.windows.first().expect("template should have a window");
+
+        SessionService::create(
+            name,
+            first.first_pane_dir(session_dir.as_deref()).as_deref(),
+        )?;
+        // a half built session would make trying again fail as a duplicate
+        let session = Target::SessionName(name.into());
+        kill_on_error(&session, SessionService::kill, || {
+            self.build_windows(&session, session_dir.as_deref())
+        })
+    }
+
+    /// names the first window of the freshly created session and adds the others
+    fn build_windows(&self, session: &Target, session_dir: Option<&Path>) -> Result<Session> {
+        let session: Target = SessionService::get_session(session)?.id.into();
+        let mut windows = self.windows.iter();
+        let first = windows.next().expect("template should have a window");
+
+        let mut window = WindowService::get_last_created_window_id(&session)?;
+        WindowService::rename(&window.into(), &first.window_name)?;
+        first.build(window, session_dir)?;
+
+        for template in windows {
+            let dir = template.first_pane_dir(session_dir);
+            WindowService::create(
+                &template.window_name,
+                &window.into(),
+                &WindowPos::After,
+                dir.as_deref(),
+            )?;
+            window = WindowService::get_last_created_window_id(&session)?;
+            template.build(window, session_dir)?;
+        }
+        SessionService::get_session(&session)
+    }
+}
+
+fn check_session_name(name: &str) -> Result<()> {
+    match name.is_empty() || name.contains([':', '.']) {
+        true => Err(anyhow!(
+            "invalid session name {name:?}, it can't be empty or contain ':' or '.'"
+        )),
+        false => Ok(()),
+    }
+}
+
+/// runs `build`, killing the session if it fails
+fn kill_on_error<T>(
+    session: &Target,
+    kill: impl FnOnce(&Target) -> Result<()>,
+    build: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    build().map_err(|e| match kill(session) {
+        Ok(()) => e,
+        Err(kill_error) => e.context(format!(
+            "session {session} is left half built, killing it failed: {kill_error:#}"
+        )),
+    })
+}
+
+fn check_dir(dir: Option<&Path>) -> Result<()> {
+    match dir {
+        Some(dir) if !dir.is_dir() => Err(anyhow!("{} is not a directory", dir.display())),
+        _ => Ok(()),
+    }
+}
+
+impl WindowTemplate {
+    fn validate(&self, session_dir: Option<&Path>) -> Result<()> {
+        if self.window_name.is_empty() {
+            return Err(anyhow!("window_name can't be empty"));
+        }
+        if let Some(layout) = &self.layout {
+            // custom layouts are the checksummed strings from #{window_layout}
+            if !LAYOUTS.contains(&layout.as_str()) && !layout.contains(',') {
+                return Err(anyhow!("unknown layout {layout:?}"));
+            }
+        }
+        let dir = self.dir(session_dir);
+        check_dir(dir.as_deref())?;
+        for pane in &self.panes {
+            check_dir(pane.dir(dir.as_deref()).as_deref())?;
+        }
+        Ok(())
+    }
+
+    fn dir(&self, session_dir: Option<&Path>) -> Option<PathBuf> {
+        resolve(self.start_directory.as_deref(), session_dir)
+    }
+
+    fn first_pane_dir(&self, session_dir: Option<&Path>) -> Option<PathBuf> {
+        let dir = self.dir(session_dir);
+        match self.panes.first() {
+            Some(pane) => pane.dir(dir.as_deref()),
+            None => dir,
+        }
+    }
+
+    /// splits the freshly created window into its panes and starts their commands
+    fn build(&self, window: IdW, session_dir: Option<&Path>) -> Result<()> {
+        let dir = self.dir(session_dir);
+        let first = PaneService::get_all(&window.into())?
+            .first()
+            .map(|p| p.id)
+            .ok_or_else(|| anyhow!("window {window} has no panes"))?;
+
+        let mut panes: Vec<IdP> = vec![first];
+        for pane in self.panes.iter().skip(1) {
+            let last = panes[panes.len() - 1];
+            let pane_dir = pane.dir(dir.as_deref());
+            panes.push(PaneService::split(
+                &last.into(),
+                &PaneSplit::Vertical,
+                pane_dir.as_deref(),
+            )?);
+            // keeps room for the next split, as tmuxp does
+            if let Some(layout) = &self.layout {
+                WindowService::select_layout(&window.into(), layout)?;
+            }
+        }
+        if let Some(layout) = &self.layout {
+            WindowService::select_layout(&window.into(), layout)?;
+        }
+
+        for (id, pane) in panes.iter().zip(&self.panes) {
+            for command in &pane.shell_command {
+                PaneService::send_keys(&(*id).into(), command)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PaneTemplate {
+    fn dir(&self, window_dir: Option<&Path>) -> Option<PathBuf> {
+        resolve(self.start_directory.as_deref(), window_dir)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, env, path::PathBuf};
+
+    use anyhow::{anyhow, Result};
+
+    use super::{kill_on_error, resolve, PaneTemplate, Template};
+    use crate::tmux::target::Target;
+
+    #[test]
+    fn parses_tmuxp_shorthands() {
+        let template: Template = r#"
+session_name: dev
+start_directory: /
+windows:
+  - window_name: editor
+    layout: main-vertical
+    panes:
+      - nvim
+      -
+      - shell_command:
+          - cd src
+          - cargo watch
+        start_directory: tmp
+  - window_name: shell
+"#
+        .parse()
+        .unwrap();
+
+        assert_eq!("dev", template.session_name);
+        assert_eq!(2, template.windows.len());
+
+        let panes = &template.windows[0].panes;
+        assert_eq!(vec!["nvim".to_string()], panes[0].shell_command);
+        assert_eq!(PaneTemplate::default(), panes[1]);
+        assert_eq!(vec!["cd src", "cargo watch"], panes[2].shell_command);
+        assert_eq!(Some("tmp".into()), panes[2].start_directory);
+        assert!(template.windows[1].panes.is_empty());
+    }
+
+    #[test]
+    fn validation_errors() {
+        let no_windows = "session_name: dev\nwindows: []";
+        assert!(no_windows.parse::<Template>().is_err());
+
+        let bad_name = "session_name: a.b\nwindows:\n  - window_name: w";
+        assert!(bad_name.parse::<Template>().is_err());
+
+        let bad_layout = "session_name: dev\nwindows:\n  - window_name: w\n    layout: diagonal";
+        let error = bad_layout.parse::<Template>().unwrap_err();
+        assert!(format!("{error:#}").contains("unknown layout"));
+
+        let missing_dir =
+            "session_name: dev\nstart_directory: /nonexistent/rts\nwindows:\n  - window_name: w";
+        let error = missing_dir.parse::<Template>().unwrap_err();
+        assert!(format!("{error:#}").contains("is not a directory"));
+
+        let missing_field = "windows:\n  - window_name: w";
+        assert!(missing_field.parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn checks_the_name_override() {
+        let template: Template = "session_name: dev\nwindows:\n  - window_name: w"
+            .parse()
+            .unwrap();
+
+        for name in ["a:b", "a.b"] {
+            let error = template.build(Some(name)).unwrap_err();
+            assert!(
+                format!("{error:#}").contains("invalid session name"),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn kills_half_built_sessions() {
+        let session = Target::SessionName("dev".into());
+        let killed = Cell::new(None);
+
+        let result: Result<()> = kill_on_error(
+            &session,
+            |target| {
+                killed.set(Some(target.clone()));
+                Ok(())
+            },
+            || Err(anyhow!("split-window failed")),
+        );
+        assert_eq!("split-window failed", format!("{:#}", result.unwrap_err()));
+        assert_eq!(Some(session.clone()), killed.take());
+
+        let result: Result<()> = kill_on_error(
+            &session,
+            |_| Err(anyhow!("no server")),
+            || Err(anyhow!("split-window failed")),
+        );
+        assert!(format!("{:#}", result.unwrap_err()).contains("left half built"));
+
+        let built = kill_on_error(&session, |_| panic!("nothing to kill"), || Ok(1));
+        assert_eq!(1, built.unwrap());
+    }
+
+    #[test]
+    fn resolves_directories() {
+        let home = PathBuf::from(env::var("HOME").unwrap());
+
+        assert_eq!(Some(home.join("code")), resolve(Some("~/code"), None));
+        assert_eq!(
+            Some(PathBuf::from("/srv/app/src")),
+            resolve(Some("src"), Some(&PathBuf::from("/srv/app")))
+        );
+        assert_eq!(
+            Some(PathBuf::from("/tmp")),
+            resolve(Some("/tmp"), Some(&PathBuf::from("/srv/app")))
+        );
+        assert_eq!(
+            Some(PathBuf::from("/srv")),
+            resolve(None, Some(&PathBuf::from("/srv")))
+        );
+        assert_eq!(None, resolve(None, None));
+    }
+}
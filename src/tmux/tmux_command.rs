@@ -1,19 +1,20 @@
 use std::{
     fmt::Display,
-    io,
-    process::{Command, Output},
+    io::{self, Read},
+    path::Path,
+    process::{Command, Output, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Result};
 
 use crate::tui::logger::Logger;
 
-use super::windows::IdW;
-const SESSION_FORMAT: &str =
-    "#{#{session_id},#S,#{?session_attached,1,},#{session_last_attached},#{session_windows},#{session_created}}";
-
-const WINDOW_FORMAT: &str =
-    "#{#{window_id},#W,#{window_active},#{window_activity},#{window_panes}}";
+use super::{
+    format::{format, PANE_FIELDS, SESSION_FIELDS, WINDOW_FIELDS},
+    target::Target,
+};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub enum WindowPos {
@@ -31,13 +32,115 @@ impl Display for WindowPos {
     }
 }
 
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum PaneSplit {
+    Horizontal,
+    #[default]
+    Vertical,
+}
+
+impl Display for PaneSplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PaneSplit::Horizontal => "-h",
+            PaneSplit::Vertical => "-v",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaneResize {
+    Up,
+    Down,
+    Left,
+    Right,
+    Zoom,
+}
+
+impl Display for PaneResize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PaneResize::Up => "-U",
+            PaneResize::Down => "-D",
+            PaneResize::Left => "-L",
+            PaneResize::Right => "-R",
+            PaneResize::Zoom => "-Z",
+        })
+    }
+}
+
 fn base_cmd() -> Command {
     let cmd = "tmux";
     Command::new(cmd)
 }
 
+/// how long tmux gets to answer a command before it's given up on
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+trait TimedOutput {
+    fn timed_output(&mut self) -> io::Result<Output>;
+}
+
+impl TimedOutput for Command {
+    /// like `output`, but kills the command once it runs longer than `TIMEOUT`
+    fn timed_output(&mut self) -> io::Result<Output> {
+        let mut child = self
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // drained on their own threads so a chatty command can't fill a pipe
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let deadline = Instant::now() + TIMEOUT;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            thread::sleep(Duration::from_millis(1));
+        };
+
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// escapes a trailing `;`, which tmux would take for the end of the command
+fn literal(arg: &str) -> String {
+    match arg.strip_suffix(';') {
+        Some(rest) => format!("{rest}\\;"),
+        None => arg.into(),
+    }
+}
+
+/// the error tmux printed to stderr, on a single line
 fn error_decorator(message: &str) -> String {
-    todo!();
+    message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 trait IoToAnyhowResult {
@@ -47,10 +150,18 @@ trait IoToAnyhowResult {
 impl IoToAnyhowResult for io::Result<Output> {
     fn as_result(self, msg: &str) -> Result<Vec<u8>> {
         match self {
-            Ok(output) => match output.status.success() {
-                true => anyhow::Result::Ok(output.stdout),
-                false => Err(anyhow!(msg.to_string())),
-            },
+            Ok(output) if output.status.success() => anyhow::Result::Ok(output.stdout),
+            Ok(output) => {
+                let error = error_decorator(&String::from_utf8_lossy(&output.stderr));
+                match error.is_empty() {
+                    true => Err(anyhow!(msg.to_string())),
+                    false => Err(anyhow!(error).context(msg.to_string())),
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => Err(anyhow!(
+                "{msg}: tmux didn't answer within {} seconds",
+                TIMEOUT.as_secs()
+            )),
             Err(_) => Err(anyhow!("command could not be run")),
         }
     }
@@ -61,115 +172,401 @@ pub struct TmuxCommand;
 impl TmuxCommand {
     pub fn get_sessions() -> Result<Vec<u8>> {
         base_cmd()
-            .args(["list-sessions", "-F", SESSION_FORMAT])
-            .output()
+            .args(["list-sessions", "-F", &format(SESSION_FIELDS)])
+            .timed_output()
             .as_result("list-sessions command failed")
     }
 
-    pub fn get_windows(session_name: &str) -> Result<Vec<u8>> {
+    pub fn get_windows(session: &Target) -> Result<Vec<u8>> {
         base_cmd()
-            .args(["list-windows", "-t", session_name, "-F", WINDOW_FORMAT])
-            .output()
-            .as_result(&format!("list-windows failed for session {session_name}",))
+            .args([
+                "list-windows",
+                "-t",
+                &session.to_string(),
+                "-F",
+                &format(WINDOW_FIELDS),
+            ])
+            .timed_output()
+            .as_result(&format!("list-windows failed for session {session}"))
     }
 
-    pub fn get_session(name: &str) -> Result<Vec<u8>> {
+    pub fn get_server_windows() -> Result<Vec<u8>> {
+        base_cmd()
+            .args(["list-windows", "-a", "-F", &format(WINDOW_FIELDS)])
+            .timed_output()
+            .as_result("list-windows command failed")
+    }
+
+    pub fn get_server_panes() -> Result<Vec<u8>> {
+        base_cmd()
+            .args(["list-panes", "-a", "-F", &format(PANE_FIELDS)])
+            .timed_output()
+            .as_result("list-panes command failed")
+    }
+
+    pub fn get_session(target: &Target) -> Result<Vec<u8>> {
         base_cmd()
             .args([
                 "list-sessions",
                 "-F",
-                SESSION_FORMAT,
+                &format(SESSION_FIELDS),
                 "-f",
-                &format!("#{{m:{name},#S}}"),
+                &target.filter(),
             ])
-            .output()
-            .as_result("get session command failed")
+            .timed_output()
+            .as_result(&format!("get session command failed for {target}"))
     }
 
-    pub fn get_window(session_name: &str, id: &IdW) -> Result<Vec<u8>> {
+    pub fn get_window(target: &Target) -> Result<Vec<u8>> {
         base_cmd()
             .args([
                 "list-windows",
+                "-a",
                 "-F",
-                WINDOW_FORMAT,
+                &format(WINDOW_FIELDS),
                 "-f",
-                &format!("#{{==:{id},#{{window_id}}}}"),
+                &target.filter(),
+            ])
+            .timed_output()
+            .as_result(&format!("get window command failed for {target}"))
+    }
+
+    /// names of the clients, control mode clients such as ours print as empty lines
+    pub fn get_clients(session: &Target) -> Result<Vec<u8>> {
+        base_cmd()
+            .args([
+                "list-clients",
                 "-t",
-                session_name,
+                &session.to_string(),
+                "-F",
+                "#{?client_control_mode,,#{client_name}}",
             ])
-            .output()
-            .as_result("get window command failed for window @{id}")
+            .timed_output()
+            .as_result(&format!("list-clients failed for session {session}"))
     }
 
-    pub fn rename_session(old_name: &str, new_name: &str) -> Result<()> {
+    /// sessions of every client, control mode clients such as ours print as empty lines
+    pub fn get_client_sessions() -> Result<Vec<u8>> {
         base_cmd()
-            .args(["rename-session", "-t", old_name, new_name])
-            .output()
-            .as_result(&format!("rename-session failed for session {old_name}",))
+            .args([
+                "list-clients",
+                "-F",
+                "#{?client_control_mode,,#{session_id}}",
+            ])
+            .timed_output()
+            .as_result("list-clients command failed")
+    }
+
+    /// control mode client that only listens for notifications, attached to the
+    /// session of `pane`
+    pub fn control_client(pane: &str) -> Command {
+        let mut cmd = base_cmd();
+        cmd.args([
+            "-C",
+            "attach-session",
+            "-f",
+            "no-output,ignore-size,read-only",
+            "-t",
+            pane,
+        ]);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        cmd
+    }
+
+    pub fn rename_session(target: &Target, new_name: &str) -> Result<()> {
+        base_cmd()
+            .args(["rename-session", "-t", &target.to_string(), new_name])
+            .timed_output()
+            .as_result(&format!("rename-session failed for session {target}"))
             .map(|_| ())
     }
 
-    pub fn rename_window(id: &IdW, new_name: &str) -> Result<()> {
+    pub fn rename_window(target: &Target, new_name: &str) -> Result<()> {
         base_cmd()
-            .args(["rename-window", "-t", &id.to_string(), new_name])
-            .output()
-            .as_result(&format!("rename-window failed for window @{id}",))
+            .args(["rename-window", "-t", &target.to_string(), new_name])
+            .timed_output()
+            .as_result(&format!("rename-window failed for window {target}"))
             .map(|_| ())
     }
 
-    pub fn attach_session(name: &str) -> Result<()> {
+    /// switches the client to the target, selecting its window and pane if given
+    pub fn attach(target: &Target) -> Result<()> {
         base_cmd()
-            .args(["switch-client", "-t", name])
-            .output()
-            .as_result(&format!("attach-session failed for session {name}"))
+            .args(["switch-client", "-t", &target.to_string()])
+            .timed_output()
+            .as_result(&format!("switch-client failed for {target}"))
             .map(|_| ())
     }
 
-    pub fn attach_window(id: &IdW) -> Result<()> {
+    /// attaches the terminal to the target, meant to be called from outside tmux
+    pub fn attach_session(target: &Target) -> Result<()> {
+        match base_cmd()
+            .args(["attach-session", "-t", &target.to_string()])
+            .status()
+        {
+            Ok(status) if status.success() => Ok(()),
+            Ok(_) => Err(anyhow!("attach-session failed for {target}")),
+            Err(_) => Err(anyhow!("command could not be run")),
+        }
+    }
+
+    pub fn set_session_option(target: &Target, option: &str, value: &str) -> Result<()> {
         base_cmd()
-            .args(["switch-client", "-t", &id.to_string()])
-            .output()
-            .as_result(&format!("select-window failed for window @{id}",))
+            .args(["set-option", "-t", &target.to_string(), option, value])
+            .timed_output()
+            .as_result(&format!("set-option {option} failed for session {target}"))
             .map(|_| ())
     }
 
-    pub fn kill_session(name: &str) -> Result<()> {
+    pub fn unset_session_option(target: &Target, option: &str) -> Result<()> {
         base_cmd()
-            .args(["kill-session", "-t", name])
-            .output()
-            .as_result(&format!("kill-session failed for session {name}",))
+            .args(["set-option", "-u", "-t", &target.to_string(), option])
+            .timed_output()
+            .as_result(&format!(
+                "set-option -u {option} failed for session {target}"
+            ))
             .map(|_| ())
     }
 
-    pub fn kill_window(id: &IdW) -> Result<()> {
+    /// sets `option` on several sessions in a single tmux call
+    pub fn set_sessions_option(option: &str, values: &[(Target, String)]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = base_cmd();
+        for (index, (target, value)) in values.iter().enumerate() {
+            if index > 0 {
+                cmd.arg(";");
+            }
+            cmd.args(["set-option", "-t", &target.to_string(), option, value]);
+        }
+        cmd.timed_output()
+            .as_result(&format!("set-option {option} failed"))
+            .map(|_| ())
+    }
+
+    pub fn swap_window(source: &Target, target: &Target) -> Result<()> {
         base_cmd()
-            .args(["kill-window", "-t", &id.to_string()])
-            .output()
-            .as_result(&format!("kill-window failed for window @{id}"))
+            .args([
+                "swap-window",
+                "-d",
+                "-s",
+                &source.to_string(),
+                "-t",
+                &target.to_string(),
+            ])
+            .timed_output()
+            .as_result(&format!(
+                "swap-window failed for windows {source} and {target}"
+            ))
             .map(|_| ())
     }
 
-    pub fn create_session(name: &str) -> Result<()> {
+    pub fn kill_session(target: &Target) -> Result<()> {
         base_cmd()
-            .args(["new-session", "-d", "-s", name])
-            .output()
+            .args(["kill-session", "-t", &target.to_string()])
+            .timed_output()
+            .as_result(&format!("kill-session failed for session {target}"))
+            .map(|_| ())
+    }
+
+    pub fn kill_window(target: &Target) -> Result<()> {
+        base_cmd()
+            .args(["kill-window", "-t", &target.to_string()])
+            .timed_output()
+            .as_result(&format!("kill-window failed for window {target}"))
+            .map(|_| ())
+    }
+
+    pub fn create_session(name: &str, dir: Option<&Path>) -> Result<()> {
+        let mut cmd = base_cmd();
+        cmd.args(["new-session", "-d", "-s", name]);
+        if let Some(dir) = dir {
+            cmd.arg("-c").arg(dir);
+        }
+        cmd.timed_output()
             .as_result(&format!("new-session failed for session {name}"))
             .map(|_| ())
     }
 
-    pub fn create_window(name: &str, id: &IdW, pos: &WindowPos) -> Result<()> {
+    pub fn create_window(
+        name: &str,
+        target: &Target,
+        pos: &WindowPos,
+        dir: Option<&Path>,
+    ) -> Result<()> {
+        let mut cmd = base_cmd();
+        cmd.args([
+            "new-window",
+            "-d",
+            &pos.to_string(),
+            "-t",
+            &target.to_string(),
+            "-n",
+            name,
+        ]);
+        if let Some(dir) = dir {
+            cmd.arg("-c").arg(dir);
+        }
+        cmd.timed_output()
+            .as_result(&format!("new-window failed for window {name}"))
+            .map(|_| ())
+    }
+
+    pub fn select_layout(target: &Target, layout: &str) -> Result<()> {
+        base_cmd()
+            .args(["select-layout", "-t", &target.to_string(), layout])
+            .timed_output()
+            .as_result(&format!("select-layout failed for window {target}"))
+            .map(|_| ())
+    }
+
+    pub fn get_panes(window: &Target) -> Result<Vec<u8>> {
         base_cmd()
             .args([
-                "new-window",
-                "-d",
-                &pos.to_string(),
+                "list-panes",
                 "-t",
-                &id.to_string(),
-                "-n",
-                name,
+                &window.to_string(),
+                "-F",
+                &format(PANE_FIELDS),
             ])
-            .output()
-            .as_result(&format!("new-window failed for window {name}"))
+            .timed_output()
+            .as_result(&format!("list-panes failed for window {window}"))
+    }
+
+    pub fn get_pane(target: &Target) -> Result<Vec<u8>> {
+        base_cmd()
+            .args([
+                "list-panes",
+                "-a",
+                "-F",
+                &format(PANE_FIELDS),
+                "-f",
+                &target.filter(),
+            ])
+            .timed_output()
+            .as_result(&format!("get pane command failed for {target}"))
+    }
+
+    pub fn kill_pane(target: &Target) -> Result<()> {
+        base_cmd()
+            .args(["kill-pane", "-t", &target.to_string()])
+            .timed_output()
+            .as_result(&format!("kill-pane failed for pane {target}"))
             .map(|_| ())
     }
+
+    pub fn split_pane(target: &Target, split: &PaneSplit, dir: Option<&Path>) -> Result<Vec<u8>> {
+        let mut cmd = base_cmd();
+        cmd.args([
+            "split-window",
+            "-d",
+            &split.to_string(),
+            "-t",
+            &target.to_string(),
+            "-P",
+            "-F",
+            "#{pane_id}",
+        ]);
+        if let Some(dir) = dir {
+            cmd.arg("-c").arg(dir);
+        }
+        cmd.timed_output()
+            .as_result(&format!("split-window failed for pane {target}"))
+    }
+
+    /// types `keys` literally into the target pane and presses enter
+    pub fn send_keys(target: &Target, keys: &str) -> Result<()> {
+        let target = target.to_string();
+        base_cmd()
+            .args(["send-keys", "-t", &target, "-l", "--", &literal(keys)])
+            .args([";", "send-keys", "-t", &target, "Enter"])
+            .timed_output()
+            .as_result(&format!("send-keys failed for {target}"))
+            .map(|_| ())
+    }
+
+    /// visible content of the pane, with its colours as escape sequences
+    pub fn capture_pane(target: &Target) -> Result<Vec<u8>> {
+        base_cmd()
+            .args(["capture-pane", "-p", "-e", "-t", &target.to_string()])
+            .timed_output()
+            .as_result(&format!("capture-pane failed for pane {target}"))
+    }
+
+    pub fn select_pane(target: &Target) -> Result<()> {
+        base_cmd()
+            .args(["select-pane", "-t", &target.to_string()])
+            .timed_output()
+            .as_result(&format!("select-pane failed for pane {target}"))
+            .map(|_| ())
+    }
+
+    pub fn rename_pane(target: &Target, title: &str) -> Result<()> {
+        base_cmd()
+            .args(["select-pane", "-t", &target.to_string(), "-T", title])
+            .timed_output()
+            .as_result(&format!("rename-pane failed for pane {target}"))
+            .map(|_| ())
+    }
+
+    pub fn resize_pane(target: &Target, resize: &PaneResize, amount: u16) -> Result<()> {
+        let mut cmd = base_cmd();
+        cmd.args([
+            "resize-pane",
+            "-t",
+            &target.to_string(),
+            &resize.to_string(),
+        ]);
+        if *resize != PaneResize::Zoom {
+            cmd.arg(amount.to_string());
+        }
+        cmd.timed_output()
+            .as_result(&format!("resize-pane failed for pane {target}"))
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::process::Command;
+
+    use super::{error_decorator, literal, IoToAnyhowResult};
+
+    #[test]
+    fn decorates_errors() {
+        assert_eq!(
+            "duplicate session: foo",
+            error_decorator("duplicate session: foo\n")
+        );
+        assert_eq!("first; second", error_decorator("  first\n\nsecond  \n"));
+        assert_eq!("", error_decorator("\n"));
+    }
+
+    #[test]
+    fn escapes_trailing_semicolons() {
+        assert_eq!(r"echo hi\;", literal("echo hi;"));
+        assert_eq!(
+            r"find . -exec echo {} \\;",
+            literal(r"find . -exec echo {} \;")
+        );
+        assert_eq!("a; b", literal("a; b"));
+        assert_eq!("-n x", literal("-n x"));
+    }
+
+    #[test]
+    fn errors_carry_stderr() {
+        let error = Command::new("sh")
+            .args(["-c", "echo 'duplicate session: foo' >&2; exit 1"])
+            .output()
+            .as_result("new-session failed")
+            .unwrap_err();
+
+        assert_eq!(
+            "new-session failed: duplicate session: foo",
+            format!("{error:#}")
+        );
+    }
 }
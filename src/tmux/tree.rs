@@ -0,0 +1,156 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::{
+    panes::{Pane, PaneService},
+    sessions::{Session, SessionService},
+    windows::{Window, WindowService},
+};
+
+/// Every session, window and pane of the tmux server, nested by ownership
+///
+/// Timestamps are unix seconds, as reported by tmux.
+#[derive(Debug, Serialize)]
+pub struct ServerTree {
+    pub generated_at: u64,
+    pub sessions: Vec<SessionNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionNode {
+    #[serde(flatten)]
+    pub session: Session,
+    pub windows: Vec<WindowNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindowNode {
+    #[serde(flatten)]
+    pub window: Window,
+    pub panes: Vec<Pane>,
+}
+
+impl ServerTree {
+    /// loads the whole tree with one tmux call per entity type
+    pub fn load() -> Result<Self> {
+        let windows = WindowService::get_server_windows()?;
+        let panes = PaneService::get_server_panes()?;
+        let sessions = SessionService::get_all()?;
+
+        Ok(Self::build(sessions, windows, panes))
+    }
+
+    fn build(sessions: Vec<Session>, windows: Vec<Window>, panes: Vec<Pane>) -> Self {
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let sessions = sessions
+            .into_iter()
+            .map(|session| SessionNode {
+                windows: windows
+                    .iter()
+                    .filter(|w| w.session_id == session.id)
+                    .map(|window| WindowNode {
+                        panes: panes
+                            .iter()
+                            .filter(|p| p.window_id == window.id)
+                            .cloned()
+                            .collect(),
+                        window: window.clone(),
+                    })
+                    .collect(),
+                session,
+            })
+            .collect();
+
+        Self {
+            generated_at,
+            sessions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::Value;
+
+    use super::ServerTree;
+    use crate::tmux::{panes::Pane, sessions::Session, windows::Window};
+
+    fn keys(value: &Value) -> Vec<&str> {
+        let mut keys: Vec<_> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn serializes_nested_tree() {
+        let sessions = vec![
+            Session::from_str("$1\x1fdev\x1f1\x1f1722892534\x1f1\x1f1722892000\x1f\x1f\x1f")
+                .unwrap(),
+            Session::from_str("$2\x1fempty\x1f0\x1f\x1f0\x1f1722892000\x1f1\x1f\x1f").unwrap(),
+        ];
+        let windows = vec![Window::from_str("@3\x1f$1\x1feditor\x1f1\x1f1722892534\x1f1").unwrap()];
+        let panes = vec![Pane::from_str(
+            "%4\x1f@3\x1f0\x1fnvim\x1f/home/user\x1f42\x1f80\x1f24\x1f1\x1ftitle",
+        )
+        .unwrap()];
+
+        let tree = ServerTree::build(sessions, windows, panes);
+        let json = serde_json::to_value(&tree).unwrap();
+
+        assert_eq!(vec!["generated_at", "sessions"], keys(&json));
+
+        let session = &json["sessions"][0];
+        assert_eq!(
+            vec![
+                "created_at",
+                "id",
+                "is_attached",
+                "is_hidden",
+                "last_attached",
+                "name",
+                "order",
+                "pinned_at",
+                "windows",
+                "windows_count"
+            ],
+            keys(session)
+        );
+        assert_eq!("$1", session["id"]);
+        assert_eq!(1722892534, session["last_attached"]);
+
+        let window = &session["windows"][0];
+        assert_eq!(
+            vec![
+                "id",
+                "is_active",
+                "last_active",
+                "name",
+                "panes",
+                "panes_count",
+                "session_id"
+            ],
+            keys(window)
+        );
+        assert_eq!("%4", window["panes"][0]["id"]);
+        assert_eq!("@3", window["panes"][0]["window_id"]);
+
+        assert_eq!(Value::Null, json["sessions"][1]["last_attached"]);
+        assert!(json["sessions"][1]["windows"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,16 +1,20 @@
 use core::str;
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, path::Path, str::FromStr};
 
-use anyhow::{Context, Error, Result};
+use anyhow::{anyhow, Context, Error, Result};
+use serde::{Serialize, Serializer};
 
 use crate::tui::logger::Logger;
 
 use super::{
+    format::{Fields, WINDOW_FIELDS},
+    sessions::IdS,
+    target::Target,
     tmux::TmuxEntity,
     tmux_command::{TmuxCommand, WindowPos},
 };
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
 pub struct IdW(usize);
 
 impl From<usize> for IdW {
@@ -34,13 +38,20 @@ impl Display for IdW {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+impl Serialize for IdW {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Window {
     pub id: IdW,
+    pub session_id: IdS,
     pub name: String,
-    is_active: bool,
-    last_active: u64,
-    panes_count: usize,
+    pub is_active: bool,
+    pub last_active: u64,
+    pub panes_count: usize,
 }
 
 impl TmuxEntity for Window {}
@@ -49,24 +60,15 @@ impl FromStr for Window {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let parts: Vec<_> = s.split(',').collect();
-
-        assert_eq!(
-            parts.len(),
-            5,
-            "should be 5 parts in list-windows format str"
-        );
+        let fields = Fields::parse(s, WINDOW_FIELDS).context("error parsing window")?;
 
         Ok(Window {
-            id: parts[0].parse().unwrap(),
-            name: parts[1].into(),
-            is_active: parts[2] == "1",
-            last_active: parts[3]
-                .parse()
-                .context("error parsing window last_active")?,
-            panes_count: parts[4]
-                .parse()
-                .context("error parsing window panes_count")?,
+            id: fields.parse_field("window_id")?,
+            session_id: fields.parse_field("session_id")?,
+            name: fields.get("window_name")?.into(),
+            is_active: fields.flag("window_active")?,
+            last_active: fields.parse_field("window_activity")?,
+            panes_count: fields.parse_field("window_panes")?,
         })
     }
 }
@@ -74,58 +76,77 @@ impl FromStr for Window {
 pub struct WindowService;
 
 impl WindowService {
-    pub fn get_all(session_name: &str) -> Result<Vec<Window>> {
-        let windows = TmuxCommand::get_windows(session_name)?;
+    pub fn get_all(session: &Target) -> Result<Vec<Window>> {
+        let windows = TmuxCommand::get_windows(session)?;
 
         str::from_utf8(&windows)
             .context("error parsing list-windows output")?
             .lines()
-            .map(|s| s.trim())
             .map(Window::from_str)
             .collect()
     }
 
-    pub fn get_window(session_name: &str, id: &IdW) -> Result<Window> {
-        let window = TmuxCommand::get_window(session_name, id)?;
+    /// windows of every session on the server
+    pub fn get_server_windows() -> Result<Vec<Window>> {
+        let windows = TmuxCommand::get_server_windows()?;
+
+        str::from_utf8(&windows)
+            .context("error parsing list-windows output")?
+            .lines()
+            .map(Window::from_str)
+            .collect()
+    }
+
+    pub fn get_window(target: &Target) -> Result<Window> {
+        let window = TmuxCommand::get_window(target)?;
 
         str::from_utf8(&window)
-            .context("error parsing get-window output")
-            .map(|s| s.trim())
-            .context("error converting str to Window")
+            .context("error parsing get-window output")?
+            .lines()
+            .next()
+            .ok_or_else(|| anyhow!("can't find window {target}"))
             .and_then(Window::from_str)
     }
 
-    pub fn get_last_created_window_id(session_name: &str) -> Result<IdW> {
-        let windows = TmuxCommand::get_windows(session_name)?;
+    pub fn get_last_created_window_id(session: &Target) -> Result<IdW> {
+        Self::get_all(session)?
+            .into_iter()
+            .map(|w| w.id)
+            .max()
+            .ok_or_else(|| anyhow!("session {session} has no windows"))
+    }
 
-        let ids: Result<Vec<IdW>> = str::from_utf8(&windows)?
-            .lines()
-            .map(|l| {
-                let (id, _) = l.split_once(',').unwrap();
-                id
-            })
-            .map(IdW::from_str)
-            .collect();
-        Ok(ids.unwrap().into_iter().max().unwrap())
+    pub fn create(name: &str, target: &Target, pos: &WindowPos, dir: Option<&Path>) -> Result<()> {
+        Logger::log(&format!("creating {target}"));
+        TmuxCommand::create_window(name, target, pos, dir)
+    }
+
+    pub fn select_layout(target: &Target, layout: &str) -> Result<()> {
+        TmuxCommand::select_layout(target, layout)
+    }
+
+    pub fn kill(target: &Target) -> Result<()> {
+        Logger::log(&format!("killing {target}"));
+        TmuxCommand::kill_window(target)
     }
 
-    pub fn create(name: &str, id: &IdW, pos: &WindowPos) -> Result<()> {
-        Logger::log(&format!("creating {id}"));
-        TmuxCommand::create_window(name, id, pos)
+    pub fn rename(target: &Target, new_name: &str) -> Result<()> {
+        Logger::log(&format!("renaming {target}"));
+        TmuxCommand::rename_window(target, new_name)
     }
 
-    pub fn kill(id: &IdW) -> Result<()> {
-        Logger::log(&format!("killing {id}"));
-        TmuxCommand::kill_window(id)
+    pub fn attach(target: &Target) -> Result<()> {
+        TmuxCommand::attach(target)
     }
 
-    pub fn rename(id: &IdW, new_name: &str) -> Result<()> {
-        Logger::log(&format!("renaming {id}"));
-        TmuxCommand::rename_window(id, new_name)
+    /// runs a command in the window's active pane by typing it out
+    pub fn send_keys(target: &Target, command: &str) -> Result<()> {
+        TmuxCommand::send_keys(target, command)
     }
 
-    pub fn attach(id: &IdW) -> Result<()> {
-        TmuxCommand::attach_window(id)
+    /// swaps the positions of two windows, the active window stays active
+    pub fn swap(source: &Target, target: &Target) -> Result<()> {
+        TmuxCommand::swap_window(source, target)
     }
 
     fn show(name: &str) -> Result<()> {
@@ -139,11 +160,12 @@ impl WindowService {
 
 #[test]
 fn from_str() {
-    let window_str = "@42,test_window,1,1722892534,4";
+    let window_str = "@42\x1f$3\x1ftest,window\x1f1\x1f1722892534\x1f4";
     let window = Window::from_str(window_str).unwrap();
 
     assert_eq!(IdW::from(42), window.id);
-    assert_eq!("test_window".to_string(), window.name);
+    assert_eq!(IdS::from(3), window.session_id);
+    assert_eq!("test,window".to_string(), window.name);
     assert!(window.is_active);
     assert_eq!(1722892534, window.last_active);
     assert_eq!(4, window.panes_count);
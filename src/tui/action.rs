@@ -1,8 +1,12 @@
 use ratatui::crossterm::event::KeyCode;
 
-use crate::tmux::{tmux_command::WindowPos, windows::IdW};
+use crate::tmux::{panes::IdP, sessions::IdS, tmux_command::WindowPos, windows::IdW};
 
-use super::{mode::Section, tmux_list::Selection};
+use super::{
+    mode::{Batch, Section},
+    tmux_list::Selection,
+    worker::{JobId, Loaded},
+};
 
 pub enum Actions<'a> {
     Tick,
@@ -14,14 +18,35 @@ pub enum Actions<'a> {
     LoadWindows,
     ClearInput,
     InputKey(KeyCode),
+    CycleTemplate,
+    SelectPicked(Selection),
+    Refresh,
+    JobDone(JobId, Result<(), String>),
+    Loaded(JobId, Result<Loaded, String>),
 
     // actions
     Create(Section, &'a str, Option<WindowPos>),
     Select(Section, Selection),
     Kill(Section),
-    RemoveSession(String),
-    RemoveWindow(String, IdW),
+    RemoveSession(IdS),
+    RemoveWindow(IdS, IdW),
+    RemovePane(IdW, IdP),
     Rename(Section, &'a str),
+    MoveUp(Section),
+    MoveDown(Section),
+    ToggleMark(Section),
+    ApplyBatch(Section, Batch),
+    PickDir(Section),
+    JumpToWindow,
+    SubmitSearch(Section),
+    Send(Section, &'a str),
+
+    // changes made to tmux outside of the app
+    SessionsChanged,
+    SessionRenamed(IdS, String),
+    WindowAdded(IdW),
+    WindowClosed(IdW),
+    WindowRenamed(IdW, String),
 
     // mode switching
     EnterCreate(Option<WindowPos>),
@@ -30,10 +55,27 @@ pub enum Actions<'a> {
     ExitRename,
     EnterDelete,
     ExitDelete,
+    EnterPick,
+    ExitPick,
+    EnterPalette,
+    ExitPalette,
+    EnterSearch,
+    EnterVisual,
+    ExitVisual,
+    EnterConfirm(Batch),
+    ExitConfirm,
+    ExitSearch,
+    EnterSend,
+    ExitSend,
     ToggleHelp,
 
     ChangeSection(Section),
     ToggleHidden,
+    ToggleSessionHidden,
+    TogglePinned,
+    CycleSort,
+    ToggleDetails,
     AttachSession,
     AttachWindow,
+    AttachPane,
 }
@@ -0,0 +1,235 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+
+/// Turns text with ANSI escapes, as printed by `capture-pane -e`, into styled
+/// ratatui text. Only SGR sequences are styled, any other escape is dropped.
+pub fn to_text(input: &str) -> Text<'static> {
+    let mut lines = vec![];
+    let mut style = Style::default();
+
+    for raw in input.lines() {
+        let mut spans = vec![];
+        let mut content = String::new();
+        let mut chars = raw.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                content.push(c);
+                continue;
+            }
+            match chars.next() {
+                // CSI, parameters up to a final byte in @..~
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut last = None;
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            last = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if last == Some('m') {
+                        if !content.is_empty() {
+                            spans.push(Span::styled(std::mem::take(&mut content), style));
+                        }
+                        style = apply_sgr(style, &params);
+                    }
+                }
+                // OSC, terminated by BEL or ST
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !content.is_empty() {
+            spans.push(Span::styled(content, style));
+        }
+        lines.push(Line::from(spans));
+    }
+    Text::from(lines)
+}
+
+/// Parameters are `;` separated, with `:` separating the sub parameters of one.
+/// Empty, out of range and unknown parameters leave the style as it is.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    if params.is_empty() {
+        return Style::default();
+    }
+    let mut groups = params.split(';');
+
+    while let Some(group) = groups.next() {
+        let mut parts = group.split(':');
+        let Ok(code) = parts.next().unwrap_or_default().parse::<u32>() else {
+            continue;
+        };
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            28 => style.remove_modifier(Modifier::HIDDEN),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(basic(code - 30)),
+            38 => match extended(group, parts, &mut groups) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            39 => style.fg(Color::Reset),
+            40..=47 => style.bg(basic(code - 40)),
+            48 => match extended(group, parts, &mut groups) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            49 => style.bg(Color::Reset),
+            90..=97 => style.fg(bright(code - 90)),
+            100..=107 => style.bg(bright(code - 100)),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// colour from the `5;n` or `2;r;g;b` that follows a 38 or 48, or from its
+/// own `:5:n` or `:2:[colour space]:r:g:b` sub parameters
+fn extended<'a>(
+    group: &str,
+    parts: impl Iterator<Item = &'a str>,
+    groups: &mut impl Iterator<Item = &'a str>,
+) -> Option<Color> {
+    if !group.contains(':') {
+        return color(groups);
+    }
+    let mut parts: Vec<&str> = parts.collect();
+    if parts.first() == Some(&"2") && parts.len() == 5 {
+        parts.remove(1);
+    }
+    color(&mut parts.into_iter())
+}
+
+fn color<'a>(values: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    let mut next = || values.next()?.parse::<u8>().ok();
+    match next()? {
+        5 => next().map(Color::Indexed),
+        2 => Some(Color::Rgb(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
+
+fn basic(index: u32) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::Gray,
+    }
+}
+
+fn bright(index: u32) -> Color {
+    match index {
+        0 => Color::DarkGray,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::{
+        style::{Color, Modifier, Style},
+        text::{Line, Span},
+    };
+
+    use super::to_text;
+
+    #[test]
+    fn styles_sgr_sequences() {
+        let text = to_text("plain \x1b[1;31mbold red\x1b[0m back\n\x1b[38;5;208morange\x1b[39m");
+
+        assert_eq!(2, text.lines.len());
+        assert_eq!(
+            Line::from(vec![
+                Span::raw("plain "),
+                Span::styled(
+                    "bold red",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                ),
+                Span::raw(" back"),
+            ]),
+            text.lines[0]
+        );
+        assert_eq!(
+            Line::from(vec![Span::styled(
+                "orange",
+                Style::default().fg(Color::Indexed(208))
+            )]),
+            text.lines[1]
+        );
+    }
+
+    #[test]
+    fn keeps_style_across_lines() {
+        let text = to_text("\x1b[48;2;1;2;3mone\ntwo\x1b[m");
+
+        let style = Style::default().bg(Color::Rgb(1, 2, 3));
+        assert_eq!(Line::from(Span::styled("one", style)), text.lines[0]);
+        assert_eq!(Line::from(Span::styled("two", style)), text.lines[1]);
+    }
+
+    #[test]
+    fn skips_malformed_parameters() {
+        let text = to_text("\x1b[1;300;;31ma\x1b[38:2::1:2:3mb\x1b[48:5:999;38;5;999;4mc");
+
+        let bold_red = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        let rgb = bold_red.fg(Color::Rgb(1, 2, 3));
+        assert_eq!(
+            Line::from(vec![
+                Span::styled("a", bold_red),
+                Span::styled("b", rgb),
+                Span::styled("c", rgb.add_modifier(Modifier::UNDERLINED)),
+            ]),
+            text.lines[0]
+        );
+        let text = to_text("\x1b[38:2:4:5:6mcolon rgb\x1b[m");
+        assert_eq!(
+            Line::from(Span::styled(
+                "colon rgb",
+                Style::default().fg(Color::Rgb(4, 5, 6))
+            )),
+            text.lines[0]
+        );
+    }
+
+    #[test]
+    fn drops_other_escapes() {
+        let text = to_text("\x1b]8;;http://x\x1b\\link\x1b]8;;\x07\x1b[2Kdone");
+
+        assert_eq!(Line::from(Span::raw("linkdone")), text.lines[0]);
+    }
+}
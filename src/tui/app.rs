@@ -1,14 +1,20 @@
+use anyhow::anyhow;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::{
     cmp,
     collections::HashMap,
     io,
+    path::Path,
     sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
 };
 
 use crate::{
     tmux::{
-        sessions::{Session, SessionService},
+        panes::{IdP, Pane, PaneService},
+        sessions::{IdS, Session, SessionService},
+        target::Target,
+        template::Template,
         tmux_command::WindowPos,
         windows::{IdW, Window, WindowService},
     },
@@ -16,197 +22,1157 @@ use crate::{
 };
 
 use super::{
+    config::{Config, Theme},
+    diff::diff,
+    dirs,
     event::Events,
-    logger::Logger,
-    mode::{Mode, Section, ToggleResult::*},
-    tmux_list::StatefulList,
+    keymap::{Command, Keymap},
+    mode::{Batch, Mode, Section, ToggleResult::*},
+    picker::{fuzzy_match, Picker},
+    preview::Preview,
+    sort::SortStrategy,
+    tmux_list::{ListEntry, StatefulList},
     tui::TUI,
+    worker::{JobId, Loaded, Worker},
 };
 
+/// how long a notification stays in the footer
+const NOTIFICATION_TIME: Duration = Duration::from_secs(5);
+
+/// how long a job runs before the footer mentions it, so quick reads don't flicker
+const PENDING_DELAY: Duration = Duration::from_millis(300);
+
+/// a job of the worker we're waiting for, with what to do once it succeeded
+///
+/// Failures of the reads catching up with changes made elsewhere aren't
+/// reported, the next one gets another chance.
+struct Pending {
+    id: JobId,
+    label: String,
+    then: Vec<A<'static>>,
+    since: Instant,
+    report: bool,
+}
+
 pub struct App {
-    pub session_list: StatefulList,
-    pub window_list: StatefulList,
-    sessions: HashMap<String, Session>,
-    windows: HashMap<String, Vec<Window>>,
+    pub session_list: StatefulList<IdS>,
+    pub window_list: StatefulList<IdW>,
+    pub pane_list: StatefulList<IdP>,
+    sessions: HashMap<IdS, Session>,
+    windows: HashMap<IdS, Vec<Window>>,
+    panes: HashMap<IdW, Vec<Pane>>,
+    templates: Vec<String>,
+    template: Option<usize>,
+    pub picker: Picker,
+    palette: Vec<IdW>,
+    pub sort: SortStrategy,
+    pub theme: Theme,
+    pub keymap: Keymap,
+    pub show_details: bool,
+    clients: Vec<String>,
+    pub preview: Preview,
+    poll_interval: Option<Duration>,
+    polled: Instant,
+    worker: Worker,
+    pending: Vec<Pending>,
+    notification: Option<(String, Instant)>,
     pub mode: Mode,
     atx: Sender<A<'static>>,
     arx: Receiver<A<'static>>,
 }
 
 impl App {
+    /// the loads go through the worker, failed ones keep what was loaded before
     fn load_sessions(&mut self) {
-        self.sessions.clear();
-        let sessions = SessionService::get_all().unwrap();
+        self.load("loading sessions".into(), vec![], true, || {
+            SessionService::get_all().map(Loaded::Sessions)
+        });
+    }
+
+    fn load_windows(&mut self, session: IdS) {
+        self.load("loading windows".into(), vec![], true, move || {
+            WindowService::get_all(&session.into()).map(|windows| Loaded::Windows(session, windows))
+        });
+    }
+
+    fn load_panes(&mut self) {
+        let Some(id) = self.selected_window().map(|w| w.id) else {
+            return;
+        };
+        self.load("loading panes".into(), vec![], true, move || {
+            PaneService::get_all(&id.into()).map(|panes| Loaded::Panes(id, panes))
+        });
+    }
+
+    /// every window of the server, for the palette and to search sessions by
+    /// their windows
+    fn load_server_windows(&mut self) {
+        self.load("loading windows".into(), vec![], true, || {
+            WindowService::get_server_windows().map(Loaded::ServerWindows)
+        });
+    }
 
-        for session in sessions {
-            self.sessions.insert(session.name.clone(), session);
+    /// takes over what the worker loaded, keeping the selections where possible
+    fn apply(&mut self, loaded: Loaded) {
+        match loaded {
+            Loaded::Sessions(sessions) => self.sessions_loaded(sessions),
+            Loaded::Session(session) => {
+                self.sessions.insert(session.id, session);
+                if !self.session_list.items.is_empty() {
+                    self.reselect_session(self.session_list.get_active_id());
+                }
+            }
+            Loaded::Windows(session, windows) => self.windows_loaded(session, windows),
+            Loaded::WindowCreated(session, id, windows) => {
+                self.windows_loaded(session, windows);
+                if self.selected_session().is_some_and(|s| s.id == session) {
+                    self.window_list.select_id(id);
+                    self.atx
+                        .send(A::Select(Section::Windows, Selection::Noop))
+                        .unwrap();
+                }
+            }
+            Loaded::ServerWindows(windows) => self.server_windows_loaded(windows),
+            Loaded::Panes(window, panes) => self.panes_loaded(window, panes),
+            Loaded::Pane(pane) => {
+                if let Some(panes) = self.panes.get_mut(&pane.window_id) {
+                    if let Some(index) = panes.iter().position(|p| p.id == pane.id) {
+                        panes[index] = pane;
+                    }
+                }
+                self.hydrate_pane_list();
+            }
+            Loaded::Clients(session, clients) => {
+                if self.selected_session().is_some_and(|s| s.id == session) {
+                    self.clients = clients;
+                }
+            }
+            Loaded::Dirs(dirs) => {
+                if let Mode::Pick(_, input) = self.mode.beneath_help() {
+                    self.picker = Picker::new(dirs);
+                    self.picker.filter(&input.content);
+                }
+            }
         }
     }
 
-    fn load_windows(&mut self) {
-        let session_name = self.session_list.get_active_item();
-        let windows = WindowService::get_all(&session_name).unwrap();
+    /// takes over the sessions the first time, afterwards only if they changed
+    fn sessions_loaded(&mut self, sessions: Vec<Session>) {
+        if self.sessions.is_empty() {
+            self.sessions = sessions.into_iter().map(|s| (s.id, s)).collect();
+            self.hydrate_session_list();
+            if !self.session_list.items.is_empty() {
+                self.session_selected();
+            }
+            return;
+        }
+        let known: Vec<Session> = self.sessions.values().cloned().collect();
+        // the order of the map means nothing, the list gets sorted anyway
+        let changes = diff(&known, &sessions, |s| s.id);
+        if !changes.added.is_empty() || !changes.removed.is_empty() || !changes.changed.is_empty() {
+            self.update_sessions(sessions);
+        }
+    }
 
-        self.windows.insert(session_name.clone(), windows);
+    /// keeps the windows of the session along with its window count, the
+    /// window list is only rebuilt if they changed
+    fn windows_loaded(&mut self, session: IdS, windows: Vec<Window>) {
+        if let Some(known) = self.sessions.get_mut(&session) {
+            known.windows_count = windows.len();
+        }
+        if let Some(known) = self.windows.get(&session) {
+            if diff(known, &windows, |w| w.id).is_empty() {
+                return;
+            }
+        }
+        self.windows.insert(session, windows);
+        self.rehydrate_windows(session);
+    }
+
+    fn server_windows_loaded(&mut self, windows: Vec<Window>) {
+        if let Mode::Palette(_, input) = self.mode.beneath_help() {
+            let names = windows
+                .iter()
+                .map(|w| match self.sessions.get(&w.session_id) {
+                    Some(session) => format!("{}:{}", session.name, w.name),
+                    None => format!("{}:{}", w.session_id, w.name),
+                })
+                .collect();
+            self.palette = windows.iter().map(|w| w.id).collect();
+            self.picker = Picker::new(names);
+            self.picker.filter(&input.content);
+        }
+
+        let mut by_session: HashMap<IdS, Vec<Window>> = HashMap::new();
+        for window in windows {
+            by_session
+                .entry(window.session_id)
+                .or_default()
+                .push(window);
+        }
+        self.windows.extend(by_session);
+        if self.search_query().is_some() {
+            self.search();
+        }
+    }
+
+    /// keeps the panes, rebuilding the pane list if it shows the window
+    fn panes_loaded(&mut self, window: IdW, panes: Vec<Pane>) {
+        self.panes.insert(window, panes);
+        if self.selected_window().is_none_or(|w| w.id != window) {
+            return;
+        }
+        let index = self.pane_list.state.selected().unwrap_or_default();
+        let selected = self.pane_list.items.get(index).map(|p| p.id);
+        self.hydrate_pane_list();
+        match selected {
+            Some(id) => self.pane_list.select_id_or_above(id, index),
+            None => self.pane_list.state.select_first(),
+        }
     }
 
     fn hydrate_session_list(&mut self) {
         let mut sessions: Vec<Session> = self.sessions.values().cloned().collect();
-        sessions.sort_by_key(|s| s.last_attached);
-        sessions.reverse();
-        sessions.rotate_left(1);
+        self.sort.sort(&mut sessions);
+
+        // never leave the list empty, even if every session got hidden elsewhere
+        if !self.session_list.shows_hidden() && sessions.iter().any(|s| !s.is_hidden) {
+            sessions.retain(|s| !s.is_hidden);
+        }
 
-        let sessions = sessions.into_iter().map(|s| s.name).collect();
+        let sessions = match self.search_query() {
+            Some(query) => self.search_sessions(query, sessions),
+            None => sessions
+                .into_iter()
+                .map(|s| (s.id, s.name).into())
+                .collect(),
+        };
         self.session_list.items(sessions);
     }
 
-    fn hydrate_window_list(&mut self) {
-        let session_name = self.session_list.get_active_item();
-        let names = self
-            .windows
-            .get(&session_name)
-            .unwrap_or_else(|| panic!("can't find windows for session {}", session_name))
-            .iter()
-            .map(|w| w.name.clone())
+    /// sessions whose name or one of whose windows' names match the query, best first
+    fn search_sessions(&self, query: &str, sessions: Vec<Session>) -> Vec<ListEntry<IdS>> {
+        let mut found: Vec<(i64, ListEntry<IdS>)> = sessions
+            .into_iter()
+            .filter_map(|s| {
+                let window_score = self
+                    .windows
+                    .get(&s.id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|w| fuzzy_match(query, &w.name))
+                    .map(|(score, _)| score)
+                    .max();
+                let (score, matches) = match (fuzzy_match(query, &s.name), window_score) {
+                    (Some((score, matches)), window_score) => {
+                        (cmp::max(score, window_score.unwrap_or(score)), matches)
+                    }
+                    (None, Some(score)) => (score, vec![]),
+                    (None, None) => return None,
+                };
+                Some((score, ListEntry::from((s.id, s.name)).with_matches(matches)))
+            })
             .collect();
+        found.sort_by_key(|(score, _)| -score);
+        found.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    fn hydrate_window_list(&mut self) {
+        let windows = self
+            .selected_session()
+            .and_then(|s| self.windows.get(&s.id))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let names: Vec<ListEntry<IdW>> = match self.search_query() {
+            Some(query) => windows
+                .iter()
+                .filter_map(|w| {
+                    fuzzy_match(query, &w.name).map(|(_, matches)| {
+                        ListEntry::from((w.id, w.name.clone())).with_matches(matches)
+                    })
+                })
+                .collect(),
+            None => vec![],
+        };
+        // sessions matched by their own name keep all of their windows
+        let names = match names.is_empty() {
+            true => windows
+                .iter()
+                .map(|w| (w.id, w.name.clone()).into())
+                .collect(),
+            false => names,
+        };
         self.window_list.items(names);
     }
 
+    fn hydrate_pane_list(&mut self) {
+        let labels = self
+            .selected_window()
+            .and_then(|w| self.panes.get(&w.id))
+            .into_iter()
+            .flatten()
+            .map(|p| (p.id, p.label()).into())
+            .collect();
+        self.pane_list.items(labels);
+    }
+
+    /// switches the client to the selected session, quitting once it did
     fn attach_session(&mut self) {
-        let current_session = self.session_list.get_active_item();
-        if let Ok(mode) =
-            SessionService::attach(&current_session).and_then(|_| self.mode.exit().into())
-        {
-            self.mode = mode;
-        }
+        let Some(session) = self.selected_session() else {
+            return;
+        };
+        let (id, label) = (session.id, format!("switching to session {}", session.name));
+        self.run_job(label, vec![A::Quit], move || {
+            SessionService::attach(&id.into())
+        });
     }
 
     fn attach_window(&mut self) {
-        let session = self.session_list.get_active_item();
-        let id = self.get_selected_window(&session).unwrap().id;
+        let Some(window) = self.selected_window() else {
+            return;
+        };
+        let (id, label) = (window.id, format!("switching to window {}", window.name));
+        self.run_job(label, vec![A::Quit], move || {
+            WindowService::attach(&id.into())
+        });
+    }
 
-        if let Ok(mode) = WindowService::attach(&id).and_then(|_| self.mode.exit().into()) {
-            self.mode = mode;
-        }
+    fn attach_pane(&mut self) {
+        let Some(id) = self.selected_pane().map(|p| p.id) else {
+            return;
+        };
+        self.run_job(
+            format!("switching to pane {id}"),
+            vec![A::Quit],
+            move || PaneService::attach(&id.into()),
+        );
     }
 
     fn rename_session(&mut self, new_name: &str) {
-        let old_name = self.session_list.get_active_item();
+        let id = self.session_list.get_active_id();
         self.atx.send(A::ExitRename).unwrap();
 
-        if SessionService::rename(&old_name, new_name).is_ok() {
-            if let Ok(session) = SessionService::get_session(new_name) {
-                self.sessions
-                    .remove(&old_name)
-                    .expect("session should be stored");
-                self.sessions.insert(new_name.into(), session);
-
-                let sesh = self
-                    .windows
-                    .remove(&old_name)
-                    .expect("session should have windows");
-                self.windows.insert(new_name.into(), sesh);
-                self.hydrate_session_list();
-            }
-        };
+        let name = new_name.to_string();
+        self.run_job(
+            format!("renaming session to {name}"),
+            vec![A::SessionRenamed(id, name.clone())],
+            move || SessionService::rename(&id.into(), &name),
+        );
     }
 
     fn rename_window(&mut self, new_name: &str) {
-        let session = self.session_list.get_active_item();
-        let id = self.get_selected_window(&session).unwrap().id;
         self.atx.send(A::ExitRename).unwrap();
+        let Some(id) = self.selected_window().map(|w| w.id) else {
+            return;
+        };
 
-        if WindowService::rename(&id, new_name).is_ok() {
-            if let Ok(window) = WindowService::get_window(&session, &id) {
-                self.windows.entry(session).and_modify(|windows| {
-                    if let Some(index) = windows.iter().position(|w| w.id == id) {
-                        windows.push(window);
-                        windows.swap_remove(index);
-                    }
-                });
-                self.hydrate_window_list();
-            }
+        let name = new_name.to_string();
+        self.run_job(
+            format!("renaming window to {name}"),
+            vec![A::WindowRenamed(id, name.clone())],
+            move || WindowService::rename(&id.into(), &name),
+        );
+    }
+
+    fn rename_pane(&mut self, title: &str) {
+        self.atx.send(A::ExitRename).unwrap();
+        let Some(id) = self.selected_pane().map(|p| p.id) else {
+            return;
         };
+
+        let title = title.to_string();
+        self.load(
+            format!("setting the pane title to {title}"),
+            vec![],
+            true,
+            move || {
+                PaneService::rename(&id.into(), &title)?;
+                PaneService::get_pane(&id.into()).map(Loaded::Pane)
+            },
+        );
     }
 
-    fn create_window(&mut self, name: &str, pos: Option<WindowPos>) {
-        self.atx.send(A::ExitCreate).unwrap();
-        let session = self.session_list.get_active_item();
-        let id = self.get_selected_window(&session).unwrap().id;
+    fn create_window(&mut self, name: &str, pos: Option<WindowPos>, dir: Option<&str>) {
+        let Some((session, id)) = self.selected_window().map(|w| (w.session_id, w.id)) else {
+            return;
+        };
         let pos = pos.unwrap_or_default();
 
-        if WindowService::create(name, &id, &pos).is_ok() {
-            let window = WindowService::get_last_created_window_id(&session)
-                .and_then(|id| WindowService::get_window(&session, &id))
-                .unwrap();
+        let name = name.to_string();
+        let dir = dir.map(String::from);
+        // the windows get reloaded to have the new one where tmux put it
+        self.load(format!("creating window {name}"), vec![], true, move || {
+            let target = session.into();
+            WindowService::create(&name, &id.into(), &pos, dir.as_deref().map(Path::new))?;
+            let created = WindowService::get_last_created_window_id(&target)?;
+            WindowService::get_all(&target)
+                .map(|windows| Loaded::WindowCreated(session, created, windows))
+        });
+    }
+
+    fn create_session(&mut self, name: &str, dir: Option<&str>) {
+        let template = self.selected_template().cloned();
 
-            self.windows.entry(session).and_modify(|windows| {
-                let current_window = windows.iter().position(|w| w.id == id).unwrap();
-                let index = match pos {
-                    WindowPos::Before => current_window,
-                    WindowPos::After => cmp::min(current_window + 1, windows.len()),
+        let name = name.to_string();
+        let dir = dir.map(String::from);
+        self.load(
+            format!("creating session {name}"),
+            // TODO: consider switching to the created sessions
+            vec![A::Select(Section::Sessions, Selection::NextNoWrap)],
+            true,
+            move || {
+                let session = match template {
+                    Some(template) => Template::load(&template)?.build(Some(&name))?,
+                    None => {
+                        SessionService::create(&name, dir.as_deref().map(Path::new))?;
+                        SessionService::get_session(&Target::SessionName(name))?
+                    }
                 };
-                windows.insert(index, window);
+                Ok(Loaded::Session(session))
+            },
+        );
+    }
+
+    /// creates a session or window in the picked directory, named after it
+    fn pick_dir(&mut self, section: Section) {
+        let Some(dir) = self.picker.selected().map(String::from) else {
+            return;
+        };
+        self.atx.send(A::ExitPick).unwrap();
+        let name = dirs::name_for(&dir);
+
+        match section {
+            Section::Sessions => self.create_session(&name, Some(&dir)),
+            Section::Windows => self.create_window(&name, None, Some(&dir)),
+            Section::Panes => {}
+        }
+    }
+
+    /// offers every window of the server as `session:window` choices, once
+    /// they're loaded
+    fn enter_palette(&mut self) {
+        let Toggled(mode) = self.mode.enter_palette() else {
+            return;
+        };
+        self.palette.clear();
+        self.picker = Picker::new(vec![]);
+        self.mode = mode;
+        self.load_server_windows();
+    }
+
+    fn exit_palette(&mut self) {
+        self.mode = self.mode.exit_palette().unwrap();
+    }
+
+    /// switches the client straight to the window picked in the palette
+    fn jump_to_window(&mut self) {
+        let Some(id) = self
+            .picker
+            .selected_index()
+            .map(|index| self.palette[index])
+        else {
+            return;
+        };
+        self.exit_palette();
+
+        self.run_job("switching windows".into(), vec![A::Quit], move || {
+            WindowService::attach(&id.into())
+        });
+    }
+
+    pub fn session(&self, id: &IdS) -> Option<&Session> {
+        self.sessions.get(id)
+    }
+
+    pub fn selected_session(&self) -> Option<&Session> {
+        let index = self.session_list.state.selected()?;
+        self.sessions.get(&self.session_list.items.get(index)?.id)
+    }
+
+    pub fn selected_window(&self) -> Option<&Window> {
+        self.get_selected_window(&self.selected_session()?.id)
+    }
+
+    fn selected_pane(&self) -> Option<&Pane> {
+        self.get_selected_pane(&self.selected_window()?.id)
+    }
+
+    pub fn active_pane(&self) -> Option<&Pane> {
+        let window = self.selected_window()?.id;
+        self.panes.get(&window)?.iter().find(|p| p.is_active)
+    }
+
+    /// clients attached to the selected session, only loaded for the details
+    pub fn clients(&self) -> &[String] {
+        &self.clients
+    }
+
+    fn load_clients(&mut self) {
+        self.clients.clear();
+        let Some(session) = self.selected_session().map(|s| s.id) else {
+            return;
+        };
+        if self.show_details {
+            self.load("loading clients".into(), vec![], true, move || {
+                SessionService::get_clients(&session.into())
+                    .map(|clients| Loaded::Clients(session, clients))
             });
-            let action = match pos {
-                WindowPos::Before => A::Select(Section::Windows, Selection::Noop),
-                WindowPos::After => A::Select(Section::Windows, Selection::NextNoWrap),
+        }
+    }
+
+    fn toggle_help(&mut self) {
+        self.mode = match self.mode.enter_help() {
+            Toggled(mode) => mode,
+            NotToggled(mode) => mode.exit_help().unwrap(),
+        };
+    }
+
+    fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+        self.load_clients();
+    }
+
+    /// shows or hides the sessions marked as hidden
+    fn toggle_hidden(&mut self) {
+        let id = self.session_list.get_active_id();
+        self.session_list.toggle_hidden();
+        self.reselect_session(id);
+    }
+
+    /// marks the selected session hidden, or visible again if it already was
+    fn toggle_session_hidden(&mut self) {
+        let id = self.session_list.get_active_id();
+        let Some(session) = self.sessions.get(&id) else {
+            return;
+        };
+        let (label, hide) = match session.is_hidden {
+            true => ("showing", false),
+            false if self.session_list.items.len() > 1 => ("hiding", true),
+            // keep at least one session in the list
+            false => return,
+        };
+
+        self.load(
+            format!("{label} session {}", session.name),
+            vec![],
+            true,
+            move || {
+                match hide {
+                    true => SessionService::hide(&id.into())?,
+                    false => SessionService::show(&id.into())?,
+                }
+                SessionService::get_session(&id.into()).map(Loaded::Session)
+            },
+        );
+    }
+
+    fn toggle_pinned(&mut self) {
+        let id = self.session_list.get_active_id();
+        let Some(session) = self.sessions.get(&id) else {
+            return;
+        };
+        let (label, pin) = match session.pinned_at {
+            Some(_) => ("unpinning", false),
+            None => ("pinning", true),
+        };
+
+        self.load(
+            format!("{label} session {}", session.name),
+            vec![],
+            true,
+            move || {
+                match pin {
+                    true => SessionService::pin(&id.into())?,
+                    false => SessionService::unpin(&id.into())?,
+                }
+                SessionService::get_session(&id.into()).map(Loaded::Session)
+            },
+        );
+    }
+
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.sort = config.sort;
+        self.theme = config.theme;
+        self.keymap = config.keymap;
+        self.poll_interval = Some(config.poll_interval)
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis);
+        self
+    }
+
+    pub fn with_sort(mut self, sort: SortStrategy) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        let id = self.session_list.get_active_id();
+        self.reselect_session(id);
+    }
+
+    /// swaps the moved sessions with their neighbours and stores the resulting
+    /// order of the whole list
+    fn move_sessions(&mut self, up: bool) {
+        let moving = moved_ids(&self.mode, &self.session_list);
+        let mut ids: Vec<IdS> = self.session_list.items.iter().map(|s| s.id).collect();
+        let is_pinned = |id: &IdS| self.sessions.get(id).is_some_and(|s| s.pinned_at.is_some());
+
+        let mut moved = false;
+        for index in move_order(&ids, &moving, up) {
+            let Some(other) = neighbour(index, ids.len(), up) else {
+                continue;
             };
-            self.atx.send(action).unwrap();
+            // pinned sessions always stay above the others
+            if moving.contains(&ids[other]) || is_pinned(&ids[index]) != is_pinned(&ids[other]) {
+                continue;
+            }
+            ids.swap(index, other);
+            moved = true;
+        }
+
+        if !moved {
+            return;
         }
+        // moved right away, the sessions get reloaded once tmux stored the order
+        for (position, id) in ids.iter().enumerate() {
+            if let Some(session) = self.sessions.get_mut(id) {
+                session.order = Some(position);
+            }
+        }
+        self.sort = SortStrategy::Manual;
+        self.reselect_session(self.session_list.get_active_id());
+        self.run_job("moving sessions".into(), vec![A::LoadSessions], move || {
+            SessionService::reorder(&ids)
+        });
     }
 
-    fn create_session(&mut self, name: &str) {
-        self.atx.send(A::ExitCreate).unwrap();
-        if SessionService::create(name).is_ok() {
-            let session = SessionService::get_session(name).unwrap();
-            self.sessions.insert(session.name.clone(), session);
+    fn move_windows(&mut self, up: bool) {
+        let session = self.session_list.get_active_id();
+        let Some(id) = self.get_selected_window(&session).map(|w| w.id) else {
+            return;
+        };
+        let moving = moved_ids(&self.mode, &self.window_list);
+        let Some(windows) = self.windows.get_mut(&session) else {
+            return;
+        };
+        let ids: Vec<IdW> = windows.iter().map(|w| w.id).collect();
 
-            // TODO: consider switching to the created sessions
-            self.atx
-                .send(A::Select(Section::Sessions, Selection::NextNoWrap))
-                .unwrap();
+        // moved right away, the windows get reloaded once tmux swapped them
+        let mut swaps = vec![];
+        for index in move_order(&ids, &moving, up) {
+            let Some(other) = neighbour(index, windows.len(), up) else {
+                continue;
+            };
+            if moving.contains(&windows[other].id) {
+                continue;
+            }
+            swaps.push((windows[index].id, windows[other].id));
+            windows.swap(index, other);
+        }
+        self.hydrate_window_list();
+        self.window_list.select_id(id);
+
+        if !swaps.is_empty() {
+            self.run_job("moving windows".into(), vec![A::LoadWindows], move || {
+                swaps
+                    .iter()
+                    .try_for_each(|(a, b)| WindowService::swap(&(*a).into(), &(*b).into()))
+            });
+        }
+    }
+
+    fn enter_send(&mut self) {
+        self.mode = self.mode.enter_send().unwrap();
+    }
+
+    fn exit_send(&mut self) {
+        self.session_list.clear_marks();
+        self.window_list.clear_marks();
+        self.mode = self.mode.exit_send().unwrap();
+    }
+
+    /// types `command` into the selected window or pane, into every window of
+    /// the selected session, or into every marked item
+    fn send_command(&mut self, section: Section, command: &str) {
+        let command = command.trim();
+        // the lists may still be loading
+        let pane = self.selected_pane().map(|p| p.id);
+        let selected = match section {
+            Section::Sessions => self.selected_session().is_some(),
+            Section::Windows => self.selected_window().is_some(),
+            Section::Panes => pane.is_some(),
+        };
+        if !selected {
+            return self.exit_send();
+        }
+        // the windows of the sessions are listed by the job
+        let sessions = match section {
+            Section::Sessions => with_marks(&self.session_list),
+            _ => vec![],
+        };
+        let mut targets: Vec<Target> = match section {
+            Section::Sessions => vec![],
+            Section::Windows => with_marks(&self.window_list)
+                .into_iter()
+                .map(Target::from)
+                .collect(),
+            Section::Panes => pane.map(Target::from).into_iter().collect(),
+        };
+
+        if !command.is_empty() {
+            let command = command.to_string();
+            self.run_job(format!("sending {command}"), vec![], move || {
+                for id in sessions {
+                    let windows = WindowService::get_all(&id.into())?;
+                    targets.extend(windows.iter().map(|w| Target::from(w.id)));
+                }
+                let errors: Vec<_> = targets
+                    .iter()
+                    .filter_map(|target| WindowService::send_keys(target, &command).err())
+                    .collect();
+                match errors.first() {
+                    None => Ok(()),
+                    Some(e) => Err(anyhow!(
+                        "sending failed for {} of {} targets: {e:#}",
+                        errors.len(),
+                        targets.len()
+                    )),
+                }
+            });
+        }
+        self.exit_send();
+    }
+
+    fn enter_visual(&mut self) {
+        if let Toggled(mode) = self.mode.enter_visual() {
+            match mode {
+                Mode::Visual(Section::Sessions) => self.session_list.start_range(),
+                Mode::Visual(Section::Windows) => self.window_list.start_range(),
+                _ => {}
+            }
+            self.mode = mode;
+        }
+    }
+
+    fn exit_visual(&mut self) {
+        self.session_list.clear_marks();
+        self.window_list.clear_marks();
+        self.mode = self.mode.exit_visual().unwrap();
+    }
+
+    fn toggle_mark(&mut self, section: Section) {
+        match section {
+            Section::Sessions => self.session_list.toggle_mark(),
+            Section::Windows => self.window_list.toggle_mark(),
+            Section::Panes => {}
+        }
+    }
+
+    /// number of marked items in the section
+    pub fn marked_count(&self, section: Section) -> usize {
+        match section {
+            Section::Sessions => self.session_list.marked().len(),
+            Section::Windows => self.window_list.marked().len(),
+            Section::Panes => 0,
+        }
+    }
+
+    fn enter_confirm(&mut self, batch: Batch) {
+        if let Mode::Visual(section) = self.mode {
+            if self.marked_count(section) > 0 {
+                self.mode = self.mode.enter_confirm(batch).unwrap();
+            }
+        }
+    }
+
+    fn exit_confirm(&mut self) {
+        self.mode = self.mode.exit_confirm().unwrap();
+    }
+
+    /// applies the batch to every marked item, hiding or pinning them all unless
+    /// they all already are, in which case they get shown or unpinned
+    fn apply_batch(&mut self, section: Section, batch: Batch) {
+        let count = self.marked_count(section);
+        let label = format!("applying to {count} items");
+        match section {
+            Section::Sessions => {
+                let ids = self.session_list.marked();
+                let sessions: Vec<&Session> =
+                    ids.iter().filter_map(|id| self.sessions.get(id)).collect();
+                let hide = sessions.iter().any(|s| !s.is_hidden);
+                let pin = sessions.iter().any(|s| s.pinned_at.is_none());
+
+                self.run_job(label, vec![A::Refresh], move || {
+                    ids.iter().try_for_each(|id| {
+                        let target = (*id).into();
+                        match batch {
+                            Batch::Kill => SessionService::kill(&target),
+                            Batch::Hide if hide => SessionService::hide(&target),
+                            Batch::Hide => SessionService::show(&target),
+                            Batch::Pin if pin => SessionService::pin(&target),
+                            Batch::Pin => SessionService::unpin(&target),
+                        }
+                    })
+                });
+            }
+            Section::Windows => {
+                let ids = self.window_list.marked();
+                self.run_job(label, vec![A::Refresh], move || {
+                    ids.iter().try_for_each(|id| match batch {
+                        Batch::Kill => WindowService::kill(&(*id).into()),
+                        Batch::Hide | Batch::Pin => Ok(()),
+                    })
+                });
+            }
+            Section::Panes => {}
+        };
+        self.exit_visual();
+    }
+
+    /// reloads the sessions and then the windows of the session selected by
+    /// then, forgetting the windows and panes of the others
+    fn refresh(&mut self) {
+        let session = self.selected_session().map(|s| s.id);
+        let window = self.selected_window().map(|w| w.id);
+        self.windows.retain(|id, _| Some(*id) == session);
+        self.panes.retain(|id, _| Some(*id) == window);
+
+        self.load(
+            "loading sessions".into(),
+            vec![A::LoadWindows],
+            true,
+            || SessionService::get_all().map(Loaded::Sessions),
+        );
+    }
+
+    /// picks up sessions created or killed elsewhere
+    fn sessions_changed(&mut self) {
+        self.load("loading sessions".into(), vec![], false, || {
+            SessionService::get_all().map(Loaded::Sessions)
+        });
+    }
+
+    /// checks for changes made outside of the TUI every `poll_interval`, in
+    /// case control mode is unavailable or doesn't announce them, like pins
+    ///
+    /// Only the sessions and the windows of the selected session are reloaded,
+    /// the lists are rebuilt if anything differs, keeping the selection.
+    fn poll(&mut self) {
+        match self.poll_interval {
+            Some(interval) if self.polled.elapsed() >= interval => {}
+            _ => return,
+        }
+        if !matches!(self.mode.beneath_help(), Mode::Select(_) | Mode::Visual(_))
+            || self.session_list.items.is_empty()
+        {
+            return;
+        }
+        // a slow server gets to answer before it's asked again
+        if !self.pending.is_empty() {
+            return;
+        }
+        self.polled = Instant::now();
+
+        // the windows follow the sessions, as the selected session may be gone
+        self.load(
+            "loading sessions".into(),
+            vec![A::LoadWindows],
+            false,
+            || SessionService::get_all().map(Loaded::Sessions),
+        );
+    }
+
+    /// replaces the sessions keeping the selected one, the windows of the
+    /// selected session are only reloaded if it's gone
+    fn update_sessions(&mut self, sessions: Vec<Session>) {
+        if sessions.is_empty() || self.session_list.items.is_empty() {
+            return;
+        }
+        let index = self.session_list.state.selected().unwrap_or_default();
+        let selected = self.session_list.get_active_id();
+
+        self.sessions = sessions.into_iter().map(|s| (s.id, s)).collect();
+        self.windows.retain(|id, _| self.sessions.contains_key(id));
+        self.hydrate_session_list();
+        self.session_list.select_id_or_above(selected, index);
+
+        if self.session_list.items.is_empty() || self.session_list.get_active_id() == selected {
+            return;
+        }
+        self.session_selected();
+    }
+
+    /// shows the windows known of the newly selected session, loading them if
+    /// there are none, and the panes of its first window
+    fn session_selected(&mut self) {
+        let session = self.session_list.get_active_id();
+        // windows that failed to load get another chance
+        if self.windows.get(&session).is_none_or(Vec::is_empty) {
+            self.load_windows(session);
+        }
+        self.hydrate_window_list();
+        self.window_list.select(Selection::Index(Some(0)));
+        self.window_selected();
+        self.load_clients();
+    }
+
+    fn window_selected(&mut self) {
+        self.load_panes();
+        self.hydrate_pane_list();
+        self.pane_list.select(Selection::Index(Some(0)));
+    }
+
+    fn session_renamed(&mut self, id: IdS, name: String) {
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return;
+        };
+        session.name = name;
+        if self.session_list.items.is_empty() {
+            return;
+        }
+        let selected = self.session_list.get_active_id();
+        self.hydrate_session_list();
+        self.session_list.select_id(selected);
+    }
+
+    /// reloads the windows of the session the window got added to
+    fn window_added(&mut self, id: IdW) {
+        self.load("loading windows".into(), vec![], false, move || {
+            let session = WindowService::get_window(&id.into())?.session_id;
+            WindowService::get_all(&session.into()).map(|windows| Loaded::Windows(session, windows))
+        });
+    }
+
+    fn window_closed(&mut self, id: IdW) {
+        self.panes.remove(&id);
+        let session = self
+            .windows
+            .iter()
+            .find(|(_, windows)| windows.iter().any(|w| w.id == id))
+            .map(|(session, _)| *session);
+        if let Some(session) = session {
+            self.reload_windows(session);
+        }
+    }
+
+    fn window_renamed(&mut self, id: IdW, name: String) {
+        let window = self.windows.values_mut().flatten().find(|w| w.id == id);
+        if let Some(window) = window {
+            window.name = name;
+            let session = window.session_id;
+            self.rehydrate_windows(session);
+        }
+    }
+
+    /// reloads the windows of a session we already know the windows of, along
+    /// with its window count
+    fn reload_windows(&mut self, session: IdS) {
+        if !self.windows.contains_key(&session) {
+            return;
+        }
+        self.load("loading windows".into(), vec![], false, move || {
+            WindowService::get_all(&session.into()).map(|windows| Loaded::Windows(session, windows))
+        });
+    }
+
+    /// rebuilds the window list if it shows the session, keeping the selected
+    /// window when it still exists, or else the one above it
+    fn rehydrate_windows(&mut self, session: IdS) {
+        if self.session_list.items.is_empty() || self.session_list.get_active_id() != session {
+            return;
+        }
+        let index = self.window_list.state.selected().unwrap_or_default();
+        let selected = self.window_list.items.get(index).map(|w| w.id);
+        self.hydrate_window_list();
+        match selected {
+            Some(id) => self.window_list.select_id_or_above(id, index),
+            None => self.window_list.state.select_first(),
+        }
+        if self.get_selected_window(&session).map(|w| w.id) != selected {
+            self.load_panes();
+            self.hydrate_pane_list();
+            self.pane_list.select(Selection::First);
         }
     }
 
+    /// rebuilds the session list keeping `id` selected, or its neighbour if
+    /// it's no longer listed
+    fn reselect_session(&mut self, id: IdS) {
+        let index = self.session_list.state.selected().unwrap_or_default();
+        self.hydrate_session_list();
+
+        let last_index = self.session_list.items.len().saturating_sub(1);
+        match self.session_list.items.iter().position(|s| s.id == id) {
+            Some(index) => self.session_list.select(Selection::Index(Some(index))),
+            None => self
+                .session_list
+                .select(Selection::Index(Some(cmp::min(index, last_index)))),
+        };
+        self.atx
+            .send(A::Select(Section::Sessions, Selection::Noop))
+            .unwrap();
+    }
+
     fn kill_session(&mut self) {
         self.atx.send(A::ExitDelete).unwrap();
-        let session = self.session_list.get_active_item();
-        if SessionService::kill(&session).is_ok() {
-            self.atx.send(A::RemoveSession(session)).unwrap();
-            self.atx
-                .send(A::Select(Section::Sessions, Selection::PrevNoWrap))
-                .unwrap();
-        }
+        let session = self.session_list.get_active_id();
+        self.run_job(
+            format!("killing session {}", self.session_list.get_active_item()),
+            vec![A::RemoveSession(session)],
+            move || SessionService::kill(&session.into()),
+        );
     }
 
-    fn remove_session(&mut self, session: &String) {
-        self.sessions.remove(session);
-        self.windows.remove(session);
+    /// forgets a killed session, unless control mode already did
+    fn remove_session(&mut self, session: &IdS) {
+        if let Some(windows) = self.windows.remove(session) {
+            for window in windows {
+                self.panes.remove(&window.id);
+            }
+        }
+        if self.sessions.remove(session).is_some() {
+            let sessions = self.sessions.values().cloned().collect();
+            self.update_sessions(sessions);
+        }
     }
 
-    fn remove_window(&mut self, session: String, id: &IdW) {
-        self.windows.entry(session.clone()).and_modify(|windows| {
+    fn remove_window(&mut self, session: IdS, id: &IdW) {
+        self.windows.entry(session).and_modify(|windows| {
             windows.retain(|w| w.id != *id);
         });
+        self.panes.remove(id);
+        self.rehydrate_windows(session);
+    }
+
+    fn remove_pane(&mut self, window: IdW, id: &IdP) {
+        self.panes.entry(window).and_modify(|panes| {
+            panes.retain(|p| p.id != *id);
+        });
     }
 
     fn kill_window(&mut self) {
-        let session = self.session_list.get_active_item();
-        let id = self.get_selected_window(&session).unwrap().id;
         self.atx.send(A::ExitDelete).unwrap();
+        let Some((session, id)) = self.selected_window().map(|w| (w.session_id, w.id)) else {
+            return;
+        };
 
-        if self.windows.get(&session).unwrap().len() == 1 {
+        if self
+            .windows
+            .get(&session)
+            .is_some_and(|windows| windows.len() == 1)
+        {
             self.atx.send(A::EnterDelete).unwrap();
             self.atx.send(A::ChangeSection(Section::Sessions)).unwrap();
             self.atx.send(A::Kill(Section::Sessions)).unwrap();
             return;
         }
-        if WindowService::kill(&id).is_ok() {
-            self.atx.send(A::RemoveWindow(session, id)).unwrap();
-            self.atx
-                .send(A::Select(Section::Windows, Selection::PrevNoWrap))
-                .unwrap();
+        self.run_job(
+            format!("killing window {}", self.window_list.get_active_item()),
+            vec![A::RemoveWindow(session, id)],
+            move || WindowService::kill(&id.into()),
+        );
+    }
+
+    fn kill_pane(&mut self) {
+        self.atx.send(A::ExitDelete).unwrap();
+        let Some((window, id)) = self.selected_pane().map(|p| (p.window_id, p.id)) else {
+            return;
+        };
+
+        if self
+            .panes
+            .get(&window)
+            .is_some_and(|panes| panes.len() == 1)
+        {
+            self.atx.send(A::EnterDelete).unwrap();
+            self.atx.send(A::ChangeSection(Section::Windows)).unwrap();
+            self.atx.send(A::Kill(Section::Windows)).unwrap();
+            return;
+        }
+        self.run_job(
+            format!("killing pane {}", self.pane_list.get_active_item()),
+            vec![
+                A::RemovePane(window, id),
+                A::Select(Section::Panes, Selection::PrevNoWrap),
+            ],
+            move || PaneService::kill(&id.into()),
+        );
+    }
+
+    /// runs `job` on the worker, the actions in `then` follow once it succeeded
+    fn run_job<F>(&mut self, label: String, then: Vec<A<'static>>, job: F)
+    where
+        F: FnOnce() -> anyhow::Result<()> + Send + 'static,
+    {
+        let id = self.worker.run(job);
+        self.wait_for(id, label, then, true);
+    }
+
+    /// runs the reads of `job` on the worker, what it loaded is taken over
+    /// before the actions in `then` follow
+    fn load<F>(&mut self, label: String, then: Vec<A<'static>>, report: bool, job: F)
+    where
+        F: FnOnce() -> anyhow::Result<Loaded> + Send + 'static,
+    {
+        let id = self.worker.load(job);
+        self.wait_for(id, label, then, report);
+    }
+
+    fn wait_for(&mut self, id: JobId, label: String, then: Vec<A<'static>>, report: bool) {
+        self.pending.push(Pending {
+            id,
+            label,
+            then,
+            since: Instant::now(),
+            report,
+        });
+    }
+
+    fn loaded(&mut self, id: JobId, result: Result<Loaded, String>) {
+        let result = result.map(|loaded| self.apply(loaded));
+        self.job_done(id, result);
+    }
+
+    fn job_done(&mut self, id: JobId, result: Result<(), String>) {
+        let Some(index) = self.pending.iter().position(|p| p.id == id) else {
+            return;
+        };
+        let pending = self.pending.remove(index);
+        match result {
+            Ok(()) => {
+                for action in pending.then {
+                    self.atx.send(action).unwrap();
+                }
+            }
+            Err(e) if pending.report => self.notify(e),
+            Err(_) => {}
+        }
+    }
+
+    fn notify(&mut self, message: String) {
+        self.notification = Some((message, Instant::now()));
+    }
+
+    /// the latest notification, until it expires
+    pub fn notification(&self) -> Option<&str> {
+        self.notification
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < NOTIFICATION_TIME)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// what the oldest job we're waiting for does, with the number of others,
+    /// once it takes a moment
+    pub fn pending(&self) -> Option<String> {
+        let first = self
+            .pending
+            .first()
+            .filter(|p| p.since.elapsed() >= PENDING_DELAY)?;
+        match self.pending.len() {
+            1 => Some(first.label.clone()),
+            count => Some(format!("{} (+{})", first.label, count - 1)),
         }
     }
 
@@ -214,22 +1180,119 @@ impl App {
         match &mut self.mode {
             Mode::Create(_, ref mut input, _) => input.handle_key(key),
             Mode::Rename(_, ref mut input) => input.handle_key(key),
+            Mode::Pick(_, ref mut input) | Mode::Palette(_, ref mut input) => {
+                input.handle_key(key);
+                self.picker.filter(&input.content);
+            }
+            Mode::Search(_, ref mut input) | Mode::Send(_, ref mut input) => input.handle_key(key),
             _ => {}
         };
+        if let Mode::Search(..) = self.mode {
+            self.search();
+        }
     }
 
     fn cancel_input(&mut self) {
         match &mut self.mode {
             Mode::Create(_, ref mut input, _) => input.clear(),
             Mode::Rename(_, ref mut input) => input.clear(),
+            Mode::Pick(_, ref mut input) | Mode::Palette(_, ref mut input) => {
+                input.clear();
+                self.picker.filter(&input.content);
+            }
+            Mode::Search(_, ref mut input) | Mode::Send(_, ref mut input) => input.clear(),
             _ => {}
         }
+        if let Mode::Search(..) = self.mode {
+            self.search();
+        }
     }
 
-    fn get_selected_window(&self, session: &String) -> Option<&Window> {
-        match self.window_list.state.selected() {
-            Some(index) => self.windows.get(session).unwrap().get(index),
-            None => None,
+    fn get_selected_window(&self, session: &IdS) -> Option<&Window> {
+        let index = self.window_list.state.selected()?;
+        let id = self.window_list.items.get(index)?.id;
+        self.windows.get(session)?.iter().find(|w| w.id == id)
+    }
+
+    fn get_selected_pane(&self, window: &IdW) -> Option<&Pane> {
+        let index = self.pane_list.state.selected()?;
+        let id = self.pane_list.items.get(index)?.id;
+        self.panes.get(window)?.iter().find(|p| p.id == id)
+    }
+
+    fn search_query(&self) -> Option<&str> {
+        match &self.mode {
+            Mode::Search(_, input) if !input.content.is_empty() => Some(&input.content),
+            _ => None,
+        }
+    }
+
+    /// narrows the lists down to the current query and selects the best match
+    fn search(&mut self) {
+        let window = self.selected_window().map(|w| w.id);
+        self.hydrate_session_list();
+        if self.session_list.items.is_empty() {
+            self.window_list.items(vec![]);
+            self.pane_list.items(vec![]);
+            return;
+        }
+        self.session_list.select(Selection::First);
+        self.hydrate_window_list();
+        self.window_list.select(Selection::First);
+        // typing mostly keeps the best match, its panes are already loaded
+        if self.selected_window().map(|w| w.id) != window {
+            self.load_panes();
+        }
+        self.hydrate_pane_list();
+        self.pane_list.select(Selection::First);
+    }
+
+    fn enter_search(&mut self) {
+        if let Toggled(mode) = self.mode.enter_search() {
+            self.mode = mode;
+            // every window is needed to match sessions by their windows
+            self.load_server_windows();
+        }
+    }
+
+    /// restores the full lists, keeping the selected session and window
+    fn exit_search(&mut self) {
+        let session = self
+            .session_list
+            .items
+            .get(self.session_list.state.selected().unwrap_or_default())
+            .map(|s| s.id);
+        let window = self
+            .window_list
+            .items
+            .get(self.window_list.state.selected().unwrap_or_default())
+            .map(|w| w.id);
+        self.mode = self.mode.exit_search().unwrap();
+
+        self.hydrate_session_list();
+        match session {
+            Some(id) => self.session_list.select_id(id),
+            None => self.session_list.state.select_first(),
+        }
+        self.hydrate_window_list();
+        match window {
+            Some(id) => self.window_list.select_id(id),
+            None => self.window_list.state.select_first(),
+        }
+        self.load_panes();
+        self.hydrate_pane_list();
+        self.pane_list.select(Selection::First);
+    }
+
+    fn submit_search(&mut self, section: Section) {
+        if self.session_list.items.is_empty() {
+            return;
+        }
+        self.exit_search();
+        match section {
+            Section::Sessions => self.attach_session(),
+            Section::Windows => self.attach_window(),
+            Section::Panes => {}
         }
     }
 
@@ -244,13 +1307,48 @@ impl App {
                     input.set_content(&self.window_list.get_active_item());
                     mode
                 }
+                Mode::Rename(Section::Panes, ref mut input) => {
+                    if let Some(pane) = self.selected_pane() {
+                        input.set_content(&pane.title);
+                    }
+                    mode
+                }
                 _ => mode,
             };
         }
     }
 
+    pub fn selected_template(&self) -> Option<&String> {
+        self.template.and_then(|index| self.templates.get(index))
+    }
+
+    /// cycles through no template and each of the available ones
+    fn cycle_template(&mut self) {
+        self.template = match self.template {
+            None if !self.templates.is_empty() => Some(0),
+            Some(index) if index + 1 < self.templates.len() => Some(index + 1),
+            _ => None,
+        };
+    }
+
     fn enter_create(&mut self, pos: Option<WindowPos>) {
         self.mode = self.mode.enter_create(pos).unwrap();
+        if let Mode::Create(Section::Sessions, ..) = self.mode {
+            self.templates = Template::list().unwrap_or_default();
+            self.template = None;
+        }
+    }
+
+    /// offers the directories to create from, once they're listed
+    fn enter_pick(&mut self) {
+        if let Toggled(mode) = self.mode.enter_pick() {
+            self.picker = Picker::new(vec![]);
+            self.template = None;
+            self.mode = mode;
+            self.load("listing directories".into(), vec![], true, || {
+                Ok(Loaded::Dirs(dirs::candidates()))
+            });
+        }
     }
 
     fn enter_delete(&mut self) {
@@ -265,6 +1363,10 @@ impl App {
         self.mode = self.mode.exit_rename().unwrap();
     }
 
+    fn exit_pick(&mut self) {
+        self.mode = self.mode.exit_pick().unwrap();
+    }
+
     fn exit_delete(&mut self) {
         self.mode = self.mode.exit_delete().unwrap();
     }
@@ -280,8 +1382,25 @@ impl Default for App {
         Self {
             session_list: Default::default(),
             window_list: Default::default(),
+            pane_list: Default::default(),
             sessions: Default::default(),
             windows: Default::default(),
+            panes: Default::default(),
+            templates: Default::default(),
+            template: Default::default(),
+            picker: Default::default(),
+            palette: Default::default(),
+            sort: Default::default(),
+            theme: Default::default(),
+            keymap: Default::default(),
+            show_details: Default::default(),
+            clients: Default::default(),
+            preview: Default::default(),
+            poll_interval: Some(Duration::from_millis(Config::default().poll_interval)),
+            polled: Instant::now(),
+            worker: Default::default(),
+            pending: Default::default(),
+            notification: Default::default(),
             mode: Default::default(),
             atx,
             arx,
@@ -291,18 +1410,44 @@ impl Default for App {
 
 impl App {
     pub fn run(&mut self, tui: &mut TUI) -> io::Result<()> {
+        self.worker = Worker::new(tui.events.tx.clone());
+
         while !self.mode.should_exit() {
             while let Ok(action) = self.arx.try_recv() {
                 self.handle_action(action);
             }
             let state = &self.mode.clone();
             let action = match tui.events.next() {
-                Events::Key(k) => App::handle_key_events(state, k),
+                // only commands are rebindable, typed text is left alone
+                Events::Key(k) => match state {
+                    Mode::Select(_) | Mode::Visual(_) => {
+                        App::handle_key_events(state, self.keymap.translate(k))
+                    }
+                    // the help key is typed as text once there is some
+                    Mode::Search(_, input)
+                    | Mode::Send(_, input)
+                    | Mode::Pick(_, input)
+                    | Mode::Palette(_, input)
+                        if input.content.is_empty()
+                            && self.keymap.command(k) == Some(Command::Help) =>
+                    {
+                        A::ToggleHelp
+                    }
+                    _ => App::handle_key_events(state, k),
+                },
                 Events::Resize(_, _) | Events::Tick => A::Tick,
                 Events::Init => A::Init,
                 Events::Quit => A::Quit,
+                Events::SessionsChanged => A::SessionsChanged,
+                Events::SessionRenamed(id, name) => A::SessionRenamed(id, name),
+                Events::WindowAdded(id) => A::WindowAdded(id),
+                Events::WindowClosed(id) => A::WindowClosed(id),
+                Events::WindowRenamed(id, name) => A::WindowRenamed(id, name),
+                Events::JobDone(id, result) => A::JobDone(id, result),
+                Events::Loaded(id, result) => A::Loaded(id, result),
             };
             self.handle_action(action);
+            self.preview.update(self.active_pane().map(|p| p.id));
 
             // draw the screen
             // TODO: decide where to interface with the view
@@ -316,41 +1461,282 @@ impl App {
         use Mode::*;
         use Section::*;
 
-        match (key, mode) {
-            // renaming & creating
+        match (key, mode) {
+            // renaming & creating
+            (
+                KeyEvent {
+                    code: Char(' '), ..
+                },
+                Rename(section, input),
+            ) => A::Rename(*section, &input.content),
+            (
+                KeyEvent {
+                    code: Char(' '), ..
+                },
+                Create(section, input, pos),
+            ) => A::Create(*section, &input.content, *pos),
+            (
+                KeyEvent {
+                    code: KeyCode::Esc, ..
+                },
+                Create(..),
+            ) => A::ExitCreate,
+            (
+                KeyEvent {
+                    code: KeyCode::Esc, ..
+                },
+                Rename(..),
+            ) => A::ExitRename,
+            (
+                KeyEvent {
+                    code: Char('w'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Rename(..) | Create(..),
+            ) => A::ClearInput,
+            (
+                KeyEvent {
+                    code: KeyCode::Tab, ..
+                },
+                Create(Sessions, ..),
+            ) => A::CycleTemplate,
+            (KeyEvent { code: key, .. }, Rename(..) | Create(..)) => A::InputKey(key),
+
+            // directory picker
             (
                 KeyEvent {
-                    code: Char(' '), ..
+                    code: KeyCode::Enter,
+                    ..
                 },
-                Rename(section, input),
-            ) => A::Rename(*section, &input.content),
+                Pick(section, _),
+            ) => A::PickDir(*section),
             (
                 KeyEvent {
-                    code: Char(' '), ..
+                    code: KeyCode::Esc, ..
                 },
-                Create(section, input, pos),
-            ) => A::Create(*section, &input.content, *pos),
+                Pick(..),
+            ) => A::ExitPick,
+            (
+                KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }
+                | KeyEvent {
+                    code: Char('n'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Pick(..) | Palette(..),
+            ) => A::SelectPicked(Selection::Next),
+            (
+                KeyEvent {
+                    code: KeyCode::Up, ..
+                }
+                | KeyEvent {
+                    code: Char('p'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Pick(..) | Palette(..),
+            ) => A::SelectPicked(Selection::Prev),
+            (
+                KeyEvent {
+                    code: Char('w'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Pick(..) | Palette(..),
+            ) => A::ClearInput,
+            (
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                },
+                Palette(..),
+            ) => A::JumpToWindow,
             (
                 KeyEvent {
                     code: KeyCode::Esc, ..
                 },
-                Create(..),
-            ) => A::ExitCreate,
+                Palette(..),
+            ) => A::ExitPalette,
+            (KeyEvent { code: key, .. }, Pick(..) | Palette(..)) => A::InputKey(key),
+
+            // searching
+            (
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                },
+                Search(section, _),
+            ) => A::SubmitSearch(*section),
             (
                 KeyEvent {
                     code: KeyCode::Esc, ..
                 },
-                Rename(..),
-            ) => A::ExitRename,
+                Search(..),
+            ) => A::ExitSearch,
+            (
+                KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }
+                | KeyEvent {
+                    code: Char('n'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Search(section, _),
+            ) => A::Select(*section, Selection::Next),
+            (
+                KeyEvent {
+                    code: KeyCode::Up, ..
+                }
+                | KeyEvent {
+                    code: Char('p'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Search(section, _),
+            ) => A::Select(*section, Selection::Prev),
+            (
+                KeyEvent {
+                    code: KeyCode::Tab, ..
+                },
+                Search(Sessions, _),
+            ) => A::ChangeSection(Windows),
+            (
+                KeyEvent {
+                    code: KeyCode::Tab, ..
+                },
+                Search(Windows, _),
+            ) => A::ChangeSection(Sessions),
             (
                 KeyEvent {
                     code: Char('w'),
                     modifiers: KeyModifiers::CONTROL,
                     ..
                 },
-                Rename(..) | Create(..),
+                Search(..),
             ) => A::ClearInput,
-            (KeyEvent { code: key, .. }, Rename(..) | Create(..)) => A::InputKey(key),
+            (KeyEvent { code: key, .. }, Search(..)) => A::InputKey(key),
+
+            // sending commands
+            (
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                },
+                Send(section, input),
+            ) => A::Send(*section, &input.content),
+            (
+                KeyEvent {
+                    code: KeyCode::Esc, ..
+                },
+                Send(..),
+            ) => A::ExitSend,
+            (
+                KeyEvent {
+                    code: Char('w'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+                Send(..),
+            ) => A::ClearInput,
+            (KeyEvent { code: key, .. }, Send(..)) => A::InputKey(key),
+
+            // visual mode and its batch actions
+            (
+                KeyEvent {
+                    code: Char('y'), ..
+                },
+                Confirm(section, batch),
+            ) => A::ApplyBatch(*section, *batch),
+            (_, Confirm(..)) => A::ExitConfirm,
+            (
+                KeyEvent {
+                    code: Char(' '), ..
+                },
+                Visual(section),
+            ) => A::ToggleMark(*section),
+            (
+                KeyEvent {
+                    code: Char('d'), ..
+                },
+                Visual(_),
+            ) => A::EnterConfirm(Batch::Kill),
+            (
+                KeyEvent {
+                    code: Char('x'), ..
+                },
+                Visual(Sessions),
+            ) => A::EnterConfirm(Batch::Hide),
+            (
+                KeyEvent {
+                    code: Char('p'), ..
+                },
+                Visual(Sessions),
+            ) => A::EnterConfirm(Batch::Pin),
+            (
+                KeyEvent {
+                    code: Char('K'), ..
+                },
+                Visual(section),
+            ) => A::MoveUp(*section),
+            (
+                KeyEvent {
+                    code: Char('J'), ..
+                },
+                Visual(section),
+            ) => A::MoveDown(*section),
+            (
+                KeyEvent {
+                    code: Char('j'), ..
+                },
+                Visual(section),
+            ) => A::Select(*section, Selection::NextNoWrap),
+            (
+                KeyEvent {
+                    code: Char('k'), ..
+                },
+                Visual(section),
+            ) => A::Select(*section, Selection::PrevNoWrap),
+            (
+                KeyEvent {
+                    code: Char('g'), ..
+                },
+                Visual(section),
+            ) => A::Select(*section, Selection::First),
+            (
+                KeyEvent {
+                    code: Char('G'), ..
+                },
+                Visual(section),
+            ) => A::Select(*section, Selection::Last),
+            (
+                KeyEvent {
+                    code: Char(':'), ..
+                },
+                Visual(_),
+            ) => A::EnterSend,
+            (
+                KeyEvent {
+                    code: Char('v') | KeyCode::Esc,
+                    ..
+                },
+                Visual(_),
+            ) => A::ExitVisual,
+            (
+                KeyEvent {
+                    code: Char('?'), ..
+                },
+                Visual(_),
+            ) => A::ToggleHelp,
+            (_, Visual(_)) => A::Tick,
+
+            // any key closes the help
+            (_, Help(_)) => A::ToggleHelp,
 
             // deletion handlers
             (
@@ -404,6 +1790,36 @@ impl App {
                 },
                 Select(Sessions),
             ) => A::ToggleHidden,
+            (
+                KeyEvent {
+                    code: Char('x'), ..
+                },
+                Select(Sessions),
+            ) => A::ToggleSessionHidden,
+            (
+                KeyEvent {
+                    code: Char('p'), ..
+                },
+                Select(Sessions),
+            ) => A::TogglePinned,
+            (
+                KeyEvent {
+                    code: Char('s'), ..
+                },
+                Select(Sessions),
+            ) => A::CycleSort,
+            (
+                KeyEvent {
+                    code: Char('K'), ..
+                },
+                Select(section @ (Sessions | Windows)),
+            ) => A::MoveUp(*section),
+            (
+                KeyEvent {
+                    code: Char('J'), ..
+                },
+                Select(section @ (Sessions | Windows)),
+            ) => A::MoveDown(*section),
             (
                 KeyEvent {
                     code: Char(' '), ..
@@ -422,6 +1838,12 @@ impl App {
                 },
                 Select(Windows),
             ) => A::ChangeSection(Sessions),
+            (
+                KeyEvent {
+                    code: Char('l'), ..
+                },
+                Select(Windows),
+            ) => A::ChangeSection(Panes),
             (
                 KeyEvent {
                     code: Char(' '), ..
@@ -433,6 +1855,24 @@ impl App {
                 Select(Windows),
             ) => A::AttachWindow,
 
+            // selection handlers for panes
+            (
+                KeyEvent {
+                    code: Char('h'), ..
+                },
+                Select(Panes),
+            ) => A::ChangeSection(Windows),
+            (
+                KeyEvent {
+                    code: Char(' '), ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                },
+                Select(Panes),
+            ) => A::AttachPane,
+
             (
                 KeyEvent {
                     code: Char('o'), ..
@@ -457,6 +1897,48 @@ impl App {
                 },
                 Select(_),
             ) => A::EnterRename,
+            (
+                KeyEvent {
+                    code: Char('z'), ..
+                },
+                Select(Sessions | Windows),
+            ) => A::EnterPick,
+            (
+                KeyEvent {
+                    code: Char('v'), ..
+                },
+                Select(Sessions | Windows),
+            ) => A::EnterVisual,
+            (
+                KeyEvent {
+                    code: Char('i'), ..
+                },
+                Select(_),
+            ) => A::ToggleDetails,
+            (
+                KeyEvent {
+                    code: Char(':'), ..
+                },
+                Select(_),
+            ) => A::EnterSend,
+            (
+                KeyEvent {
+                    code: Char('?'), ..
+                },
+                Select(_),
+            ) => A::ToggleHelp,
+            (
+                KeyEvent {
+                    code: Char('/'), ..
+                },
+                Select(Sessions | Windows),
+            ) => A::EnterSearch,
+            (
+                KeyEvent {
+                    code: Char('f'), ..
+                },
+                Select(_),
+            ) => A::EnterPalette,
 
             (
                 KeyEvent {
@@ -475,52 +1957,251 @@ impl App {
         use A::*;
 
         match action {
-            Tick => {}
-            Init => {
-                self.load_sessions();
-                self.hydrate_session_list();
-                self.load_windows();
-                self.hydrate_window_list();
-            }
+            Tick => self.poll(),
+            Init => self.load_sessions(),
             Quit => self.exit(),
             LoadSessions => self.load_sessions(),
-            LoadWindows => self.load_windows(),
-            Create(Section::Sessions, name, _) => self.create_session(name),
-            Create(Section::Windows, name, pos) => self.create_window(name, pos),
+            LoadWindows => {
+                if let Some(session) = self.selected_session().map(|s| s.id) {
+                    self.load_windows(session);
+                }
+            }
+            Create(Section::Sessions, name, _) => {
+                self.atx.send(ExitCreate).unwrap();
+                self.create_session(name, None);
+            }
+            Create(Section::Windows, name, pos) => {
+                self.atx.send(ExitCreate).unwrap();
+                self.create_window(name, pos, None);
+            }
+            Create(Section::Panes, ..) => {}
+            // nothing to select when a search matches nothing
+            Select(..) if self.session_list.items.is_empty() => {}
             Select(Section::Sessions, selection) => {
                 if self.sessions.len() > 1 {
                     self.hydrate_session_list();
-                    let session = self.session_list.select(selection);
-                    if !self.windows.contains_key(&session) {
-                        self.load_windows();
-                    }
-                    self.hydrate_window_list();
-                    self.window_list.select(Selection::Index(Some(0)));
+                    self.session_list.select(selection);
+                    self.session_selected();
                 }
             }
             Select(Section::Windows, selection) => {
                 self.hydrate_window_list();
                 self.window_list.select(selection);
+                self.window_selected();
+            }
+            Select(Section::Panes, selection) => {
+                self.hydrate_pane_list();
+                self.pane_list.select(selection);
             }
             Kill(Section::Sessions) => self.kill_session(),
             Kill(Section::Windows) => self.kill_window(),
+            Kill(Section::Panes) => self.kill_pane(),
             RemoveSession(session) => self.remove_session(&session),
             RemoveWindow(window, id) => self.remove_window(window, &id),
+            RemovePane(window, id) => self.remove_pane(window, &id),
             Rename(Section::Sessions, name) => self.rename_session(name),
             Rename(Section::Windows, name) => self.rename_window(name),
-            ToggleHelp => todo!(),
+            Rename(Section::Panes, name) => self.rename_pane(name),
+            PickDir(section) => self.pick_dir(section),
+            MoveUp(Section::Sessions) => self.move_sessions(true),
+            MoveDown(Section::Sessions) => self.move_sessions(false),
+            MoveUp(Section::Windows) => self.move_windows(true),
+            MoveDown(Section::Windows) => self.move_windows(false),
+            ToggleMark(section) => self.toggle_mark(section),
+            ApplyBatch(section, batch) => self.apply_batch(section, batch),
+            MoveUp(Section::Panes) | MoveDown(Section::Panes) => {}
+            JumpToWindow => self.jump_to_window(),
+            SubmitSearch(section) => self.submit_search(section),
+            Send(section, command) => self.send_command(section, command),
+            SessionsChanged => self.sessions_changed(),
+            SessionRenamed(id, name) => self.session_renamed(id, name),
+            WindowAdded(id) => self.window_added(id),
+            WindowClosed(id) => self.window_closed(id),
+            WindowRenamed(id, name) => self.window_renamed(id, name),
+            SelectPicked(selection) => self.picker.select(selection),
+            Refresh => self.refresh(),
+            JobDone(id, result) => self.job_done(id, result),
+            Loaded(id, result) => self.loaded(id, result),
+            ToggleHelp => self.toggle_help(),
             ChangeSection(section) => self.mode = self.mode.change_section(section),
             ClearInput => self.cancel_input(),
             InputKey(key) => self.input_key(key),
+            CycleTemplate => self.cycle_template(),
             EnterCreate(pos) => self.enter_create(pos),
             EnterRename => self.enter_rename(),
             EnterDelete => self.enter_delete(),
             ExitCreate => self.exit_create(),
             ExitRename => self.exit_rename(),
             ExitDelete => self.exit_delete(),
-            ToggleHidden => todo!(),
+            EnterPick => self.enter_pick(),
+            ExitPick => self.exit_pick(),
+            EnterVisual => self.enter_visual(),
+            ExitVisual => self.exit_visual(),
+            EnterConfirm(batch) => self.enter_confirm(batch),
+            ExitConfirm => self.exit_confirm(),
+            EnterPalette => self.enter_palette(),
+            ExitPalette => self.exit_palette(),
+            EnterSearch => self.enter_search(),
+            ExitSearch => self.exit_search(),
+            EnterSend => self.enter_send(),
+            ExitSend => self.exit_send(),
+            ToggleHidden => self.toggle_hidden(),
+            ToggleSessionHidden => self.toggle_session_hidden(),
+            TogglePinned => self.toggle_pinned(),
+            CycleSort => self.cycle_sort(),
+            ToggleDetails => self.toggle_details(),
             AttachSession => self.attach_session(),
             AttachWindow => self.attach_window(),
+            AttachPane => self.attach_pane(),
+        };
+    }
+}
+
+/// index of the item above or below `index`, if there's one
+fn neighbour(index: usize, len: usize, up: bool) -> Option<usize> {
+    match up {
+        true => index.checked_sub(1),
+        false => Some(index + 1).filter(|next| *next < len),
+    }
+}
+
+/// the marked items if there are any, the selected one otherwise
+fn with_marks<I: Copy + PartialEq>(list: &StatefulList<I>) -> Vec<I> {
+    match list.marked() {
+        marked if marked.is_empty() => vec![list.get_active_id()],
+        marked => marked,
+    }
+}
+
+/// the marked items in visual mode, the selected one otherwise
+fn moved_ids<I: Copy + PartialEq>(mode: &Mode, list: &StatefulList<I>) -> Vec<I> {
+    match mode {
+        Mode::Visual(_) => list.marked(),
+        _ => vec![list.get_active_id()],
+    }
+}
+
+/// indices of the moved items, the ones closest to where they move to first
+fn move_order<I: PartialEq>(ids: &[I], moving: &[I], up: bool) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..ids.len())
+        .filter(|index| moving.contains(&ids[*index]))
+        .collect();
+    if !up {
+        indices.reverse();
+    }
+    indices
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::App;
+    use crate::{
+        tmux::{sessions::Session, windows::Window},
+        tui::{
+            action::Actions as A,
+            input::InputState,
+            keymap::{Command, Keymap},
+            mode::{Mode, Section},
+            tmux_list::Selection,
+            worker::Loaded,
+        },
+    };
+
+    #[test]
+    fn sends_nothing_while_loading() {
+        let mut app = App::default();
+        for section in [Section::Sessions, Section::Windows, Section::Panes] {
+            app.mode = Mode::Select(section);
+            app.enter_send();
+            app.send_command(section, "git pull");
+            assert!(matches!(app.mode, Mode::Select(_)), "{section:?}");
+        }
+    }
+
+    #[test]
+    fn takes_over_loaded_data() {
+        let mut app = App::default();
+        app.apply(Loaded::Sessions(vec![
+            Session::from_str("$1\x1fdev\x1f1\x1f1722892534\x1f2\x1f1722892000\x1f\x1f\x1f")
+                .unwrap(),
+            Session::from_str("$2\x1fops\x1f0\x1f\x1f1\x1f1722892000\x1f\x1f\x1f").unwrap(),
+        ]));
+        assert_eq!(2, app.session_list.items.len());
+
+        let session = app.session_list.get_active_id();
+        let window = |id: usize, name: &str| {
+            Window::from_str(&format!(
+                "@{id}\x1f{session}\x1f{name}\x1f0\x1f1722892534\x1f1"
+            ))
+            .unwrap()
         };
+        app.apply(Loaded::Windows(
+            session,
+            vec![window(1, "a"), window(2, "b")],
+        ));
+        app.window_list.select(Selection::Last);
+        assert_eq!("b", app.window_list.get_active_item());
+
+        // a reload keeps the selected window and updates the window count
+        app.apply(Loaded::Windows(
+            session,
+            vec![window(2, "b"), window(3, "c"), window(4, "d")],
+        ));
+        assert_eq!(3, app.window_list.items.len());
+        assert_eq!("b", app.window_list.get_active_item());
+        assert_eq!(3, app.session(&session).unwrap().windows_count);
+
+        // windows of another session leave the list alone
+        let other = app
+            .session_list
+            .items
+            .iter()
+            .find(|s| s.id != session)
+            .unwrap()
+            .id;
+        app.apply(Loaded::Windows(other, vec![]));
+        assert_eq!(3, app.window_list.items.len());
+    }
+
+    #[test]
+    fn fills_the_picker_once_dirs_are_listed() {
+        let mut app = App::default();
+        let mut input = InputState::default();
+        input.set_content("pro");
+        app.mode = Mode::Pick(Section::Sessions, input);
+
+        // what was typed in the meantime filters them
+        app.apply(Loaded::Dirs(vec![
+            "/tmp".into(),
+            "/home/me/projects".into(),
+        ]));
+        assert_eq!(Some("/home/me/projects"), app.picker.selected());
+
+        // the picker was closed before they came
+        app.mode = Mode::default();
+        app.apply(Loaded::Dirs(vec!["/srv".into()]));
+        assert_eq!(Some("/home/me/projects"), app.picker.selected());
+    }
+
+    /// the help and the hints are generated from the keymap, so each command
+    /// should do something exactly in the sections it claims to apply to
+    #[test]
+    fn keymap_matches_key_handling() {
+        let keymap = Keymap::default();
+        for section in [Section::Sessions, Section::Windows, Section::Panes] {
+            let mode = Mode::Select(section);
+            for command in Command::ALL {
+                for key in keymap.keys(command) {
+                    let action = App::handle_key_events(&mode, key);
+                    assert_eq!(
+                        command.applies(section),
+                        !matches!(action, A::Tick),
+                        "{command} while selecting {section:?}"
+                    );
+                }
+            }
+        }
     }
 }
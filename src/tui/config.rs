@@ -0,0 +1,239 @@
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Context, Error, Result};
+use ratatui::{style::Color, widgets::BorderType};
+use serde::{Deserialize, Deserializer};
+
+use crate::tmux::template::config_dir;
+
+use super::{
+    keymap::{Command, Keymap, Keys},
+    sort::SortStrategy,
+};
+
+pub fn config_file() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// Settings of the TUI, read from `config.toml` in the config directory
+///
+/// ```toml
+/// tick_rate = 250
+/// poll_interval = 2000
+/// sort = "alphabetical"
+///
+/// [theme]
+/// border = "rounded"
+/// active = "#a6e3a1"
+///
+/// [keys]
+/// down = ["j", "ctrl-n"]
+/// quit = "Q"
+/// ```
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// milliseconds between two refreshes of the screen when nothing happens
+    pub tick_rate: u64,
+    /// milliseconds between two checks for changes made outside of the TUI,
+    /// 0 turns them off and leaves it to control mode, which only runs inside tmux
+    pub poll_interval: u64,
+    /// default order of the sessions, the `--sort` flag takes precedence
+    pub sort: SortStrategy,
+    pub theme: Theme,
+    pub keymap: Keymap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tick_rate: 250,
+            poll_interval: 2000,
+            sort: Default::default(),
+            theme: Default::default(),
+            keymap: Default::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    tick_rate: Option<u64>,
+    poll_interval: Option<u64>,
+    sort: Option<SortStrategy>,
+    theme: Theme,
+    keys: HashMap<Command, Keys>,
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let raw: RawConfig = toml::from_str(s)?;
+        let tick_rate = raw.tick_rate.unwrap_or(Config::default().tick_rate);
+        if !(10..=10_000).contains(&tick_rate) {
+            return Err(anyhow!(
+                "tick_rate should be between 10 and 10000 milliseconds, got {tick_rate}"
+            ));
+        }
+        let poll_interval = raw.poll_interval.unwrap_or(Config::default().poll_interval);
+        if poll_interval != 0 && poll_interval < 100 {
+            return Err(anyhow!(
+                "poll_interval should be 0 or at least 100 milliseconds, got {poll_interval}"
+            ));
+        }
+
+        Ok(Self {
+            tick_rate,
+            poll_interval,
+            sort: raw.sort.unwrap_or_default(),
+            theme: raw.theme,
+            keymap: Keymap::new(&raw.keys).context("invalid keys")?,
+        })
+    }
+}
+
+impl Config {
+    /// reads the config file, a missing file gives the defaults
+    pub fn load() -> Result<Self> {
+        let path = config_file();
+        match fs::read_to_string(&path) {
+            Ok(content) => content
+                .parse()
+                .with_context(|| format!("invalid config {}", path.display())),
+            Err(_) if !path.exists() => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("can't read config {}", path.display())),
+        }
+    }
+}
+
+/// Colours and borders of the TUI, colours are names, `#rrggbb` or indexes
+///
+/// * `active`: selected names and the borders of prompts creating things
+/// * `prompt`: prompts, highlighted matches and pins
+/// * `danger`: killing things
+/// * `rename`: renaming things
+/// * `search`: searching
+/// * `visual`: visual mode
+/// * `marked`: background of the marked items
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    #[serde(deserialize_with = "border")]
+    pub border: BorderType,
+    #[serde(deserialize_with = "color")]
+    pub active: Color,
+    #[serde(deserialize_with = "color")]
+    pub prompt: Color,
+    #[serde(deserialize_with = "color")]
+    pub danger: Color,
+    #[serde(deserialize_with = "color")]
+    pub rename: Color,
+    #[serde(deserialize_with = "color")]
+    pub search: Color,
+    #[serde(deserialize_with = "color")]
+    pub visual: Color,
+    #[serde(deserialize_with = "color")]
+    pub marked: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: BorderType::Thick,
+            active: Color::Green,
+            prompt: Color::Yellow,
+            danger: Color::Red,
+            rename: Color::Magenta,
+            search: Color::Blue,
+            visual: Color::Cyan,
+            marked: Color::DarkGray,
+        }
+    }
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name).map_err(|_| serde::de::Error::custom(format!("invalid color {name:?}")))
+}
+
+fn border<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<BorderType, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match name.as_str() {
+        "plain" => Ok(BorderType::Plain),
+        "rounded" => Ok(BorderType::Rounded),
+        "double" => Ok(BorderType::Double),
+        "thick" => Ok(BorderType::Thick),
+        _ => Err(serde::de::Error::custom(format!(
+            "invalid border {name:?}, expected plain, rounded, double or thick"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::{style::Color, widgets::BorderType};
+
+    use super::Config;
+    use crate::tui::sort::SortStrategy;
+
+    #[test]
+    fn parses_config() {
+        let config: Config = r##"
+tick_rate = 100
+poll_interval = 0
+sort = "alphabetical"
+
+[theme]
+border = "rounded"
+active = "#102030"
+marked = "238"
+
+[keys]
+down = ["n", "ctrl-n"]
+"##
+        .parse()
+        .unwrap();
+
+        assert_eq!(100, config.tick_rate);
+        assert_eq!(0, config.poll_interval);
+        assert_eq!(SortStrategy::Alphabetical, config.sort);
+        assert_eq!(BorderType::Rounded, config.theme.border);
+        assert_eq!(Color::Rgb(16, 32, 48), config.theme.active);
+        assert_eq!(Color::Indexed(238), config.theme.marked);
+        assert_eq!(Color::Yellow, config.theme.prompt);
+    }
+
+    #[test]
+    fn empty_config_is_default() {
+        let config: Config = "".parse().unwrap();
+
+        assert_eq!(250, config.tick_rate);
+        assert_eq!(2000, config.poll_interval);
+        assert_eq!(SortStrategy::Recent, config.sort);
+    }
+
+    #[test]
+    fn validation_errors() {
+        let errors = [
+            ("tick_rate = 0", "tick_rate should be between"),
+            (
+                "poll_interval = 50",
+                "poll_interval should be 0 or at least",
+            ),
+            ("sort = \"random\"", "unknown variant"),
+            ("colour = \"red\"", "unknown field"),
+            ("[theme]\nactive = \"greenish\"", "invalid color"),
+            ("[theme]\nborder = \"dotted\"", "invalid border"),
+            ("[keys]\njump = \"j\"", "unknown variant"),
+            ("[keys]\nup = \"j\"", "bound to both down and up"),
+        ];
+        for (config, message) in errors {
+            let error = config.parse::<Config>().unwrap_err();
+            assert!(
+                format!("{error:#}").contains(message),
+                "{config:?} failed with {error:#}"
+            );
+        }
+    }
+}
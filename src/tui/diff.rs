@@ -0,0 +1,94 @@
+/// Changes between two snapshots of the same entities, matched by id
+///
+/// * `added`: ids only in the new snapshot
+/// * `removed`: ids only in the old snapshot
+/// * `changed`: ids in both whose entity differs, renames among them
+/// * `reordered`: whether the entities in both come in another order
+#[derive(Debug, PartialEq)]
+pub struct Diff<I> {
+    pub added: Vec<I>,
+    pub removed: Vec<I>,
+    pub changed: Vec<I>,
+    pub reordered: bool,
+}
+
+impl<I> Diff<I> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.reordered
+    }
+}
+
+pub fn diff<T: PartialEq, I: PartialEq + Copy>(
+    old: &[T],
+    new: &[T],
+    id: impl Fn(&T) -> I,
+) -> Diff<I> {
+    let find = |items: &[T], wanted: I| items.iter().position(|item| id(item) == wanted);
+
+    let added = new
+        .iter()
+        .map(&id)
+        .filter(|i| find(old, *i).is_none())
+        .collect();
+    let removed = old
+        .iter()
+        .map(&id)
+        .filter(|i| find(new, *i).is_none())
+        .collect();
+    let changed = new
+        .iter()
+        .filter(|item| find(old, id(item)).is_some_and(|index| old[index] != **item))
+        .map(&id)
+        .collect();
+
+    let kept = |items: &[T], others: &[T]| -> Vec<I> {
+        items
+            .iter()
+            .map(&id)
+            .filter(|i| find(others, *i).is_some())
+            .collect()
+    };
+    let reordered = kept(old, new) != kept(new, old);
+
+    Diff {
+        added,
+        removed,
+        changed,
+        reordered,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff, Diff};
+
+    #[test]
+    fn finds_changes() {
+        let old = [(1, "a"), (2, "b"), (3, "c")];
+        let new = [(1, "a"), (3, "renamed"), (4, "d")];
+
+        assert_eq!(
+            Diff {
+                added: vec![4],
+                removed: vec![2],
+                changed: vec![3],
+                reordered: false,
+            },
+            diff(&old, &new, |item| item.0)
+        );
+    }
+
+    #[test]
+    fn finds_reorders() {
+        let old = [(1, "a"), (2, "b"), (3, "c")];
+        let new = [(3, "c"), (1, "a"), (2, "b")];
+
+        let changes = diff(&old, &new, |item| item.0);
+        assert!(changes.reordered);
+        assert!(changes.added.is_empty() && changes.removed.is_empty());
+        assert!(diff(&old, &old, |item| item.0).is_empty());
+    }
+}
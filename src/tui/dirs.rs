@@ -0,0 +1,126 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::tmux::template::config_dir;
+
+/// File listing the roots whose subdirectories are offered when zoxide isn't
+/// available, one directory per line
+pub fn roots_file() -> PathBuf {
+    config_dir().join("roots")
+}
+
+/// Directories to pick from, ranked by zoxide or else listed from the roots
+pub fn candidates() -> Vec<String> {
+    match zoxide() {
+        Some(dirs) if !dirs.is_empty() => dirs,
+        _ => from_roots(&roots()),
+    }
+}
+
+fn zoxide() -> Option<Vec<String>> {
+    let output = Command::new("zoxide").args(["query", "-l"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let dirs = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    Some(dirs)
+}
+
+fn home() -> PathBuf {
+    env::var_os("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+/// roots from the roots file, defaulting to the home directory
+fn roots() -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = fs::read_to_string(roots_file())
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.strip_prefix('~') {
+            Some(rest) => home().join(rest.trim_start_matches('/')),
+            None => PathBuf::from(line),
+        })
+        .collect();
+
+    match roots.is_empty() {
+        true => vec![home()],
+        false => roots,
+    }
+}
+
+/// each root followed by its visible subdirectories
+fn from_roots(roots: &[PathBuf]) -> Vec<String> {
+    let mut dirs = vec![];
+    for root in roots.iter().filter(|root| root.is_dir()) {
+        dirs.push(root.to_string_lossy().into_owned());
+
+        let mut children: Vec<String> = fs::read_dir(root)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| !name.to_string_lossy().starts_with('.'))
+            })
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        children.sort();
+        dirs.extend(children);
+    }
+    dirs
+}
+
+/// session or window name for a directory, tmux doesn't allow `.` or `:` in them
+pub fn name_for(dir: &str) -> String {
+    let name = Path::new(dir)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| dir.to_string());
+    name.replace(['.', ':'], "_")
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use super::{from_roots, name_for};
+
+    #[test]
+    fn names_from_dirs() {
+        assert_eq!(
+            "rusty-tmux-sessions",
+            name_for("/home/user/rusty-tmux-sessions")
+        );
+        assert_eq!("_dotfiles", name_for("/home/user/.dotfiles/"));
+        assert_eq!("app_v1_2", name_for("/srv/app:v1.2"));
+        assert_eq!("/", name_for("/"));
+    }
+
+    #[test]
+    fn lists_visible_subdirectories() {
+        let root = env::temp_dir().join(format!("rts-roots-{}", std::process::id()));
+        for dir in ["b", "a", ".hidden"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("file"), "").unwrap();
+
+        let dirs = from_roots(&[root.clone(), root.join("missing")]);
+        fs::remove_dir_all(&root).unwrap();
+
+        let root = root.to_string_lossy();
+        assert_eq!(
+            vec![root.to_string(), format!("{root}/a"), format!("{root}/b")],
+            dirs
+        );
+    }
+}
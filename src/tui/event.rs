@@ -6,6 +6,14 @@ use std::{
 
 use ratatui::crossterm::event::{self, KeyEvent};
 
+use crate::tmux::{
+    control::{self, Notification},
+    sessions::IdS,
+    windows::IdW,
+};
+
+use super::worker::{JobId, Loaded};
+
 pub struct EventHandler {
     pub tx: Sender<Events>,
     rx: Receiver<Events>,
@@ -31,6 +39,18 @@ impl EventHandler {
             sender.send(event).unwrap();
         });
 
+        let sender = tx.clone();
+        control::listen(move |notification| {
+            let event = match notification {
+                Notification::SessionsChanged => Events::SessionsChanged,
+                Notification::SessionRenamed(id, name) => Events::SessionRenamed(id, name),
+                Notification::WindowAdd(id) => Events::WindowAdded(id),
+                Notification::WindowClose(id) => Events::WindowClosed(id),
+                Notification::WindowRenamed(id, name) => Events::WindowRenamed(id, name),
+            };
+            sender.send(event).is_ok()
+        });
+
         Self { tx, rx }
     }
 
@@ -39,7 +59,7 @@ impl EventHandler {
     }
 }
 
-#[derive(Clone, Debug, Copy, Default)]
+#[derive(Clone, Debug, Default)]
 pub enum Events {
     #[default]
     Tick,
@@ -47,4 +67,15 @@ pub enum Events {
     Resize(u16, u16),
     Init,
     Quit,
+
+    // changes made to tmux outside of the app
+    SessionsChanged,
+    SessionRenamed(IdS, String),
+    WindowAdded(IdW),
+    WindowClosed(IdW),
+    WindowRenamed(IdW, String),
+
+    // a job of the worker finished, failing with the error message
+    JobDone(JobId, Result<(), String>),
+    Loaded(JobId, Result<Loaded, String>),
 }
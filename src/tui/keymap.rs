@@ -0,0 +1,610 @@
+use std::{collections::HashMap, fmt::Display};
+
+use anyhow::{anyhow, Result};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use super::mode::{Mode, Section};
+
+/// Something a key can be bound to in the config, named in kebab-case
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Command {
+    Down,
+    Up,
+    First,
+    Last,
+    Left,
+    Right,
+    Attach,
+    New,
+    NewBefore,
+    Rename,
+    Delete,
+    Search,
+    Palette,
+    PickDir,
+    Visual,
+    Details,
+    Send,
+    Hide,
+    ToggleHidden,
+    Pin,
+    Sort,
+    MoveUp,
+    MoveDown,
+    Help,
+    Quit,
+}
+
+impl Command {
+    pub const ALL: [Command; 25] = [
+        Command::Down,
+        Command::Up,
+        Command::First,
+        Command::Last,
+        Command::Left,
+        Command::Right,
+        Command::Attach,
+        Command::New,
+        Command::NewBefore,
+        Command::Rename,
+        Command::Delete,
+        Command::Search,
+        Command::Palette,
+        Command::PickDir,
+        Command::Visual,
+        Command::Details,
+        Command::Send,
+        Command::Hide,
+        Command::ToggleHidden,
+        Command::Pin,
+        Command::Sort,
+        Command::MoveUp,
+        Command::MoveDown,
+        Command::Help,
+        Command::Quit,
+    ];
+
+    /// the commands hinted at in the footer
+    const HINTS: [Command; 7] = [
+        Command::Attach,
+        Command::New,
+        Command::Rename,
+        Command::Delete,
+        Command::Search,
+        Command::Visual,
+        Command::Help,
+    ];
+
+    /// the commands hinted at in the footer in visual mode
+    const VISUAL_HINTS: [Command; 7] = [
+        Command::Delete,
+        Command::Hide,
+        Command::Pin,
+        Command::MoveUp,
+        Command::MoveDown,
+        Command::Send,
+        Command::Help,
+    ];
+
+    /// whether the command does something while selecting in `section`
+    pub fn applies(self, section: Section) -> bool {
+        use Command::*;
+        use Section::*;
+        match self {
+            Left => section != Sessions,
+            Right => section != Panes,
+            NewBefore => section == Windows,
+            New | Search | PickDir | Visual | MoveUp | MoveDown => section != Panes,
+            Hide | ToggleHidden | Pin | Sort => section == Sessions,
+            _ => true,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        use Command::*;
+        match self {
+            Down => "select the next item",
+            Up => "select the previous item",
+            First => "select the first item",
+            Last => "select the last item",
+            Left => "go to the section on the left",
+            Right => "go to the section on the right",
+            Attach => "switch to the selected item",
+            New => "create a session, or a window after the selected one",
+            NewBefore => "create a window before the selected one",
+            Rename => "rename the selected item",
+            Delete => "kill the selected item",
+            Search => "fuzzy search sessions and windows",
+            Palette => "jump to any window",
+            PickDir => "create from a directory",
+            Visual => "mark items for batch actions",
+            Details => "show or hide the details panel",
+            Send => "type a command into the selected windows",
+            Hide => "hide or show the selected session",
+            ToggleHidden => "list or unlist hidden sessions",
+            Pin => "pin or unpin the selected session",
+            Sort => "cycle the session order",
+            MoveUp => "move the selected item up",
+            MoveDown => "move the selected item down",
+            Help => "show or hide this help",
+            Quit => "quit",
+        }
+    }
+
+    /// what the command does to the marked items in visual mode, if anything
+    fn visual(self, section: Section) -> Option<&'static str> {
+        use Command::*;
+        match self {
+            Down | Up | First | Last | MoveUp | MoveDown | Help => Some(self.description()),
+            Delete => Some("kill the marked items"),
+            Hide if section == Section::Sessions => Some("hide or show the marked sessions"),
+            Pin if section == Section::Sessions => Some("pin or unpin the marked sessions"),
+            Send => Some("type a command into the marked items"),
+            Visual => Some("leave visual mode"),
+            _ => None,
+        }
+    }
+
+    /// the key `App::handle_key_events` knows the command by
+    fn builtin(self) -> KeyEvent {
+        use Command::*;
+        let code = match self {
+            Down => KeyCode::Char('j'),
+            Up => KeyCode::Char('k'),
+            First => KeyCode::Char('g'),
+            Last => KeyCode::Char('G'),
+            Left => KeyCode::Char('h'),
+            Right => KeyCode::Char('l'),
+            Attach => KeyCode::Enter,
+            New => KeyCode::Char('o'),
+            NewBefore => KeyCode::Char('O'),
+            Rename => KeyCode::Char('c'),
+            Delete => KeyCode::Char('d'),
+            Search => KeyCode::Char('/'),
+            Palette => KeyCode::Char('f'),
+            PickDir => KeyCode::Char('z'),
+            Visual => KeyCode::Char('v'),
+            Details => KeyCode::Char('i'),
+            Send => KeyCode::Char(':'),
+            Hide => KeyCode::Char('x'),
+            ToggleHidden => KeyCode::Char('H'),
+            Pin => KeyCode::Char('p'),
+            Sort => KeyCode::Char('s'),
+            MoveUp => KeyCode::Char('K'),
+            MoveDown => KeyCode::Char('J'),
+            Help => KeyCode::Char('?'),
+            Quit => KeyCode::Char('q'),
+        };
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+}
+
+/// the kebab-case name used in the config
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, c) in format!("{self:?}").chars().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                f.write_str("-")?;
+            }
+            write!(f, "{}", c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// One key or a list of them
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Keys of the commands, the built-in ones unless the config rebinds them
+///
+/// Configured keys are translated back into the built-in key of their command
+/// so the key handling itself stays the same whatever the bindings are.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeyEvent, Command)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Command::ALL.iter().map(|c| (c.builtin(), *c)).collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// the built-in bindings with the commands in `keys` rebound
+    pub fn new(keys: &HashMap<Command, Keys>) -> Result<Self> {
+        let mut bindings: Vec<(KeyEvent, Command)> = Keymap::default()
+            .bindings
+            .into_iter()
+            .filter(|(_, command)| !keys.contains_key(command))
+            .collect();
+
+        let mut rebound: Vec<(&Command, &Keys)> = keys.iter().collect();
+        rebound.sort_by_key(|(command, _)| Command::ALL.iter().position(|c| c == *command));
+
+        for (command, keys) in rebound {
+            let keys = match keys {
+                Keys::One(key) => vec![key.as_str()],
+                Keys::Many(keys) => keys.iter().map(String::as_str).collect(),
+            };
+            for name in keys {
+                let key = parse_key(name).map_err(|e| anyhow!("{e} for {command}"))?;
+                if let Some((_, other)) = bindings.iter().find(|(k, _)| *k == key) {
+                    return Err(anyhow!("{name:?} is bound to both {other} and {command}"));
+                }
+                bindings.push((key, *command));
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    pub fn keys(&self, command: Command) -> Vec<KeyEvent> {
+        self.bindings
+            .iter()
+            .filter(|(_, c)| *c == command)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    /// the keys of what can be done in `mode`, with what they do
+    pub fn help(&self, mode: &Mode) -> Vec<(String, &'static str)> {
+        match mode {
+            Mode::Select(section) => {
+                self.bound(|command| command.applies(*section).then(|| command.description()))
+            }
+            Mode::Visual(section) => {
+                let mut help = vec![("space".into(), "mark or unmark the selected item")];
+                help.extend(self.bound(|command| command.visual(*section)));
+                help.push(("esc".into(), "leave visual mode"));
+                help
+            }
+            Mode::Search(..) | Mode::Send(..) | Mode::Pick(..) | Mode::Palette(..) => {
+                let mut help: Vec<(String, &str)> = typing(mode)
+                    .into_iter()
+                    .map(|(keys, description)| (keys.into(), description))
+                    .collect();
+                help.extend(self.bound(|command| {
+                    (command == Command::Help).then_some("show this help, before typing anything")
+                }));
+                help
+            }
+            _ => vec![],
+        }
+    }
+
+    /// every bound command `describe` knows, with its keys
+    fn bound<F>(&self, describe: F) -> Vec<(String, &'static str)>
+    where
+        F: Fn(Command) -> Option<&'static str>,
+    {
+        Command::ALL
+            .into_iter()
+            .filter_map(|command| {
+                let description = describe(command)?;
+                let keys: Vec<String> = self.keys(command).into_iter().map(key_name).collect();
+                (!keys.is_empty()).then(|| (keys.join(", "), description))
+            })
+            .collect()
+    }
+
+    /// short reminders of the main commands for the footer
+    pub fn hints(&self, mode: &Mode) -> String {
+        let (before, commands, after) = match mode {
+            Mode::Select(section) => (
+                None,
+                Command::HINTS
+                    .into_iter()
+                    .filter(|command| command.applies(*section))
+                    .collect(),
+                None,
+            ),
+            Mode::Visual(section) => (
+                Some("space mark"),
+                Command::VISUAL_HINTS
+                    .into_iter()
+                    .filter(|command| command.visual(*section).is_some())
+                    .collect(),
+                Some("esc cancel"),
+            ),
+            _ => (None, vec![], None),
+        };
+        let hints = commands.into_iter().filter_map(|command: Command| {
+            let key = self.keys(command).into_iter().next()?;
+            Some(format!("{} {command}", key_name(key)))
+        });
+        before
+            .map(String::from)
+            .into_iter()
+            .chain(hints)
+            .chain(after.map(String::from))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// the command `key` is bound to
+    pub fn command(&self, key: KeyEvent) -> Option<Command> {
+        let key = normalize(key);
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, command)| *command)
+    }
+
+    /// the built-in key for the command `key` is bound to, or a key that does
+    /// nothing when `key` is the built-in key of a command that got rebound
+    pub fn translate(&self, key: KeyEvent) -> KeyEvent {
+        if let Some(command) = self.command(key) {
+            return command.builtin();
+        }
+        let key = normalize(key);
+        match Command::ALL.iter().any(|c| c.builtin() == key) {
+            true => KeyEvent::new(KeyCode::Null, KeyModifiers::NONE),
+            false => key,
+        }
+    }
+}
+
+/// the fixed keys of the modes where text is typed, which aren't rebindable
+fn typing(mode: &Mode) -> Vec<(&'static str, &'static str)> {
+    let mut keys = match mode {
+        Mode::Search(..) => vec![
+            ("enter", "go to the selected match"),
+            ("down, ctrl-n", "select the next match"),
+            ("up, ctrl-p", "select the previous match"),
+            ("tab", "search the other section"),
+        ],
+        Mode::Pick(..) => vec![
+            ("enter", "create from the selected directory"),
+            ("down, ctrl-n", "select the next directory"),
+            ("up, ctrl-p", "select the previous directory"),
+        ],
+        Mode::Palette(..) => vec![
+            ("enter", "switch to the selected window"),
+            ("down, ctrl-n", "select the next window"),
+            ("up, ctrl-p", "select the previous window"),
+        ],
+        _ => vec![(
+            "enter",
+            "type the command into the selected or marked items",
+        )],
+    };
+    keys.extend([("ctrl-w", "clear the input"), ("esc", "cancel")]);
+    keys
+}
+
+/// drops shift, which is already part of the character, and any state flags
+fn normalize(key: KeyEvent) -> KeyEvent {
+    KeyEvent::new(
+        key.code,
+        key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+    )
+}
+
+/// how keys are written in the config
+pub fn key_name(key: KeyEvent) -> String {
+    let code = match key.code {
+        KeyCode::Enter => "enter".into(),
+        KeyCode::Esc => "esc".into(),
+        KeyCode::Tab => "tab".into(),
+        KeyCode::Backspace => "backspace".into(),
+        KeyCode::Char(' ') => "space".into(),
+        KeyCode::Up => "up".into(),
+        KeyCode::Down => "down".into(),
+        KeyCode::Left => "left".into(),
+        KeyCode::Right => "right".into(),
+        KeyCode::Home => "home".into(),
+        KeyCode::End => "end".into(),
+        KeyCode::PageUp => "pageup".into(),
+        KeyCode::PageDown => "pagedown".into(),
+        KeyCode::Char(c) => c.to_string(),
+        code => format!("{code:?}").to_lowercase(),
+    };
+    let ctrl = match key.modifiers.contains(KeyModifiers::CONTROL) {
+        true => "ctrl-",
+        false => "",
+    };
+    let alt = match key.modifiers.contains(KeyModifiers::ALT) {
+        true => "alt-",
+        false => "",
+    };
+    format!("{ctrl}{alt}{code}")
+}
+
+/// parses keys such as `j`, `G`, `ctrl-n`, `alt-enter` or `space`
+pub fn parse_key(key: &str) -> Result<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = key;
+    loop {
+        if let Some(r) = rest.strip_prefix("ctrl-").filter(|r| !r.is_empty()) {
+            modifiers |= KeyModifiers::CONTROL;
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("alt-").filter(|r| !r.is_empty()) {
+            modifiers |= KeyModifiers::ALT;
+            rest = r;
+        } else {
+            break;
+        }
+    }
+
+    let code = match rest {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "space" => KeyCode::Char(' '),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(anyhow!("invalid key {key:?}")),
+            }
+        }
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{key_name, parse_key, Command, Keymap, Keys};
+    use crate::tui::mode::{Mode, Section};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(key(KeyCode::Char('J')), parse_key("J").unwrap());
+        assert_eq!(key(KeyCode::Char(' ')), parse_key("space").unwrap());
+        assert_eq!(key(KeyCode::Char('-')), parse_key("-").unwrap());
+        assert_eq!(
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::ALT),
+            parse_key("ctrl-alt-enter").unwrap()
+        );
+        assert!(parse_key("ctrl-").is_err());
+        assert!(parse_key("jk").is_err());
+
+        for name in ["j", "G", "space", "ctrl-alt-enter", "alt-?", "pagedown"] {
+            assert_eq!(name, key_name(parse_key(name).unwrap()));
+        }
+    }
+
+    #[test]
+    fn help_follows_bindings() {
+        let keys = HashMap::from([
+            (Command::Down, Keys::Many(vec!["n".into(), "ctrl-n".into()])),
+            (Command::Rename, Keys::Many(vec![])),
+        ]);
+        let keymap = Keymap::new(&keys).unwrap();
+
+        let help = keymap.help(&Mode::Select(Section::Panes));
+        assert_eq!(
+            ("n, ctrl-n".to_string(), Command::Down.description()),
+            help[0]
+        );
+        assert!(!help
+            .iter()
+            .any(|(_, d)| *d == Command::Rename.description()));
+        assert!(!help.iter().any(|(_, d)| *d == Command::Pin.description()));
+        assert!(keymap
+            .help(&Mode::Select(Section::Sessions))
+            .contains(&("p".to_string(), Command::Pin.description())));
+
+        assert_eq!(
+            "enter attach  d delete  ? help",
+            keymap.hints(&Mode::Select(Section::Panes))
+        );
+    }
+
+    #[test]
+    fn visual_help_follows_bindings() {
+        let keys = HashMap::from([
+            (Command::Delete, Keys::One("X".into())),
+            (Command::Help, Keys::One("ctrl-h".into())),
+        ]);
+        let keymap = Keymap::new(&keys).unwrap();
+
+        assert_eq!(
+            "space mark  X delete  x hide  p pin  K move-up  J move-down  : send  ctrl-h help  esc cancel",
+            keymap.hints(&Mode::Visual(Section::Sessions))
+        );
+        assert_eq!(
+            "space mark  X delete  K move-up  J move-down  : send  ctrl-h help  esc cancel",
+            keymap.hints(&Mode::Visual(Section::Windows))
+        );
+
+        let help = keymap.help(&Mode::Visual(Section::Windows));
+        assert!(help.contains(&("X".to_string(), "kill the marked items")));
+        assert!(!help.iter().any(|(keys, _)| keys == "x"));
+
+        let help = keymap.help(&Mode::Search(Section::Sessions, Default::default()));
+        assert_eq!(("enter".to_string(), "go to the selected match"), help[0]);
+        assert_eq!(
+            Some(&(
+                "ctrl-h".to_string(),
+                "show this help, before typing anything"
+            )),
+            help.last()
+        );
+    }
+
+    #[test]
+    fn translates_rebound_keys() {
+        let keys = HashMap::from([
+            (Command::Down, Keys::Many(vec!["n".into(), "ctrl-n".into()])),
+            (Command::Quit, Keys::One("Q".into())),
+        ]);
+        let keymap = Keymap::new(&keys).unwrap();
+
+        assert_eq!(
+            key(KeyCode::Char('j')),
+            keymap.translate(key(KeyCode::Char('n')))
+        );
+        assert_eq!(
+            key(KeyCode::Char('j')),
+            keymap.translate(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL))
+        );
+        // shift comes with the uppercase character
+        assert_eq!(
+            key(KeyCode::Char('q')),
+            keymap.translate(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT))
+        );
+        // the old keys no longer do anything, other keys go through
+        assert_eq!(
+            key(KeyCode::Null),
+            keymap.translate(key(KeyCode::Char('j')))
+        );
+        assert_eq!(
+            key(KeyCode::Null),
+            keymap.translate(key(KeyCode::Char('q')))
+        );
+        assert_eq!(
+            key(KeyCode::Char('k')),
+            keymap.translate(key(KeyCode::Char('k')))
+        );
+        assert_eq!(key(KeyCode::Tab), keymap.translate(key(KeyCode::Tab)));
+    }
+
+    #[test]
+    fn rejects_conflicts() {
+        let taken = HashMap::from([(Command::Down, Keys::One("k".into()))]);
+        let error = Keymap::new(&taken).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("\"k\" is bound to both up and down"));
+
+        // swapping keys is fine
+        let swapped = HashMap::from([
+            (Command::Down, Keys::One("k".into())),
+            (Command::Up, Keys::One("j".into())),
+        ]);
+        let keymap = Keymap::new(&swapped).unwrap();
+        assert_eq!(
+            key(KeyCode::Char('j')),
+            keymap.translate(key(KeyCode::Char('k')))
+        );
+
+        let invalid = HashMap::from([(Command::Up, Keys::One("hyper-k".into()))]);
+        assert!(Keymap::new(&invalid).is_err());
+    }
+}
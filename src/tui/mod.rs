@@ -8,3 +8,12 @@ pub mod view;
 pub mod tui;
 pub mod event;
 pub mod action;
+pub mod picker;
+pub mod dirs;
+pub mod sort;
+pub mod ansi;
+pub mod preview;
+pub mod keymap;
+pub mod config;
+pub mod diff;
+pub mod worker;
@@ -9,6 +9,15 @@ pub enum Section {
     #[default]
     Sessions,
     Windows,
+    Panes,
+}
+
+/// Action applied to every marked item at once
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Batch {
+    Kill,
+    Hide,
+    Pin,
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -17,7 +26,14 @@ pub enum Mode {
     Create(Section, InputState, Option<WindowPos>),
     Delete(Section),
     Rename(Section, InputState),
-    Help,
+    Pick(Section, InputState),
+    Search(Section, InputState),
+    Palette(Section, InputState),
+    Visual(Section),
+    Confirm(Section, Batch),
+    Send(Section, InputState),
+    /// the help over the mode it was opened from
+    Help(Box<Mode>),
     Exit,
 }
 
@@ -66,6 +82,7 @@ impl Mode {
             Self::Select(_) => Self::Select(section),
             Self::Delete(_) => Self::Delete(section),
             Self::Create(_, input, pos) => Self::Create(section, input.clone(), *pos),
+            Self::Search(_, input) => Self::Search(section, input.clone()),
             m => m.clone(),
         }
     }
@@ -96,6 +113,67 @@ impl Mode {
         }
     }
 
+    pub fn enter_pick(&self) -> ToggleResult {
+        match self {
+            Self::Select(s @ (Section::Sessions | Section::Windows)) => {
+                Toggled(Self::Pick(*s, InputState::default()))
+            }
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn enter_search(&self) -> ToggleResult {
+        match self {
+            Self::Select(s @ (Section::Sessions | Section::Windows)) => {
+                Toggled(Self::Search(*s, InputState::default()))
+            }
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn enter_palette(&self) -> ToggleResult {
+        match self {
+            Self::Select(s) => Toggled(Self::Palette(*s, InputState::default())),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn enter_visual(&self) -> ToggleResult {
+        match self {
+            Self::Select(s @ (Section::Sessions | Section::Windows)) => Toggled(Self::Visual(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn enter_confirm(&self, batch: Batch) -> ToggleResult {
+        match (self, batch) {
+            (Self::Visual(s @ Section::Sessions), _) | (Self::Visual(s), Batch::Kill) => {
+                Toggled(Self::Confirm(*s, batch))
+            }
+            (v, _) => NotToggled(v.clone()),
+        }
+    }
+
+    /// sends to the selection, or to the marked items when coming from visual mode
+    pub fn enter_send(&self) -> ToggleResult {
+        match self {
+            Self::Select(s) | Self::Visual(s) => Toggled(Self::Send(*s, InputState::default())),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn enter_help(&self) -> ToggleResult {
+        match self {
+            Self::Select(_)
+            | Self::Visual(_)
+            | Self::Search(..)
+            | Self::Send(..)
+            | Self::Pick(..)
+            | Self::Palette(..) => Toggled(Self::Help(Box::new(self.clone()))),
+            v => NotToggled(v.clone()),
+        }
+    }
+
     pub fn exit_create(&self) -> ToggleResult {
         match self {
             Self::Create(s, ..) => Toggled(Self::Select(*s)),
@@ -117,6 +195,64 @@ impl Mode {
         }
     }
 
+    pub fn exit_pick(&self) -> ToggleResult {
+        match self {
+            Self::Pick(s, _) => Toggled(Self::Select(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn exit_search(&self) -> ToggleResult {
+        match self {
+            Self::Search(s, _) => Toggled(Self::Select(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn exit_palette(&self) -> ToggleResult {
+        match self {
+            Self::Palette(s, _) => Toggled(Self::Select(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn exit_visual(&self) -> ToggleResult {
+        match self {
+            Self::Visual(s) | Self::Confirm(s, _) => Toggled(Self::Select(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    /// goes back to visual mode to change the marks
+    pub fn exit_confirm(&self) -> ToggleResult {
+        match self {
+            Self::Confirm(s, _) => Toggled(Self::Visual(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    pub fn exit_send(&self) -> ToggleResult {
+        match self {
+            Self::Send(s, _) => Toggled(Self::Select(*s)),
+            v => NotToggled(v.clone()),
+        }
+    }
+
+    /// the mode the help was opened from, or else the mode itself
+    pub fn beneath_help(&self) -> &Mode {
+        match self {
+            Self::Help(mode) => mode,
+            mode => mode,
+        }
+    }
+
+    pub fn exit_help(&self) -> ToggleResult {
+        match self {
+            Self::Help(mode) => Toggled(*mode.clone()),
+            v => NotToggled(v.clone()),
+        }
+    }
+
     pub fn exit(&self) -> ToggleResult {
         match self {
             Self::Select(_) => Toggled(Self::Exit),
@@ -152,9 +288,12 @@ impl Mode {
 
 #[cfg(test)]
 mod test {
-    use crate::tui::mode::Section;
+    use crate::tui::mode::{Section, ToggleResult::Toggled};
 
-    use super::Mode::{self, *};
+    use super::{
+        Batch,
+        Mode::{self, *},
+    };
 
     #[test]
     fn correct_toggling_create() {
@@ -223,6 +362,141 @@ mod test {
         assert!(!not_toggled.was_toggled());
     }
 
+    #[test]
+    fn correct_toggling_pick() {
+        let windows = Mode::default().change_section(Section::Windows);
+
+        let toggled = windows.enter_pick();
+        assert!(toggled.was_toggled());
+
+        let toggled = toggled.unwrap().exit_pick();
+        assert!(toggled.was_toggled());
+        assert_eq!(windows, toggled.unwrap());
+    }
+
+    #[test]
+    fn incorrect_toggling_pick() {
+        let panes = Mode::default().change_section(Section::Panes);
+        assert!(!panes.enter_pick().was_toggled());
+
+        let create = Mode::default().enter_create(None).unwrap();
+        assert!(!create.enter_pick().was_toggled());
+        assert!(!create.exit_pick().was_toggled());
+    }
+
+    #[test]
+    fn correct_toggling_search() {
+        let default = Mode::default();
+
+        let toggled = default.enter_search();
+        assert!(toggled.was_toggled());
+
+        let searching = toggled.unwrap().change_section(Section::Windows);
+        assert!(matches!(searching, Search(Section::Windows, _)));
+
+        let toggled = searching.exit_search();
+        assert!(toggled.was_toggled());
+        assert_eq!(Select(Section::Windows), toggled.unwrap());
+    }
+
+    #[test]
+    fn incorrect_toggling_search() {
+        let panes = Mode::default().change_section(Section::Panes);
+        assert!(!panes.enter_search().was_toggled());
+
+        let renaming = Mode::default().enter_rename().unwrap();
+        assert!(!renaming.enter_search().was_toggled());
+        assert!(!renaming.exit_search().was_toggled());
+    }
+
+    #[test]
+    fn correct_toggling_palette() {
+        let panes = Mode::default().change_section(Section::Panes);
+
+        let toggled = panes.enter_palette();
+        assert!(toggled.was_toggled());
+
+        let toggled = toggled.unwrap().exit_palette();
+        assert_eq!(Toggled(panes), toggled);
+    }
+
+    #[test]
+    fn incorrect_toggling_palette() {
+        let deleting = Mode::default().enter_delete().unwrap();
+
+        assert!(!deleting.enter_palette().was_toggled());
+        assert!(!deleting.exit_palette().was_toggled());
+    }
+
+    #[test]
+    fn correct_toggling_visual() {
+        let visual = Mode::default().enter_visual();
+        assert!(visual.was_toggled());
+
+        let confirm = visual.unwrap().enter_confirm(Batch::Pin);
+        assert_eq!(Toggled(Confirm(Section::Sessions, Batch::Pin)), confirm);
+
+        let visual = confirm.unwrap().exit_confirm();
+        assert_eq!(Toggled(Visual(Section::Sessions)), visual);
+
+        let confirm = visual.unwrap().enter_confirm(Batch::Kill).unwrap();
+        assert_eq!(Toggled(Mode::default()), confirm.exit_visual());
+    }
+
+    #[test]
+    fn incorrect_toggling_visual() {
+        let panes = Mode::default().change_section(Section::Panes);
+        assert!(!panes.enter_visual().was_toggled());
+        assert!(!Mode::default().enter_confirm(Batch::Kill).was_toggled());
+
+        // only sessions can be hidden or pinned
+        let windows = Visual(Section::Windows);
+        assert!(!windows.enter_confirm(Batch::Hide).was_toggled());
+        assert!(windows.enter_confirm(Batch::Kill).was_toggled());
+    }
+
+    #[test]
+    fn correct_toggling_send() {
+        let panes = Mode::default().change_section(Section::Panes);
+        assert!(panes.enter_send().was_toggled());
+
+        let sending = Visual(Section::Windows).enter_send().unwrap();
+        assert!(matches!(sending, Send(Section::Windows, _)));
+        assert_eq!(Toggled(Select(Section::Windows)), sending.exit_send());
+    }
+
+    #[test]
+    fn incorrect_toggling_send() {
+        let deleting = Mode::default().enter_delete().unwrap();
+
+        assert!(!deleting.enter_send().was_toggled());
+        assert!(!deleting.exit_send().was_toggled());
+        assert!(!Confirm(Section::Sessions, Batch::Kill)
+            .enter_send()
+            .was_toggled());
+    }
+
+    #[test]
+    fn toggling_help() {
+        let windows = Mode::default().change_section(Section::Windows);
+
+        let help = windows.enter_help();
+        assert_eq!(Toggled(Help(Box::new(windows.clone()))), help);
+        assert_eq!(Toggled(windows), help.unwrap().exit_help());
+
+        // typed text is still there after the help
+        let mut searching = Mode::default().enter_search().unwrap();
+        if let Search(_, input) = &mut searching {
+            input.set_content("dev");
+        }
+        let help = searching.enter_help().unwrap();
+        assert_eq!(Toggled(searching), help.exit_help());
+
+        let renaming = Mode::default().enter_rename().unwrap();
+        assert!(!renaming.enter_help().was_toggled());
+        assert!(!renaming.exit_help().was_toggled());
+    }
+
     #[test]
     fn exit() {
         let (selecting, creating, renaming, deleting) = (
@@ -0,0 +1,175 @@
+use std::cmp;
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ratatui::widgets::ListState;
+
+use super::tmux_list::Selection;
+
+/// An item that matched the query, with the char indices that matched it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub index: usize,
+    pub score: i64,
+    pub indices: Vec<usize>,
+}
+
+/// fuzzy matches `query` against `candidate`, an empty query matches everything
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, vec![]));
+    }
+    SkimMatcherV2::default()
+        .smart_case()
+        .fuzzy_indices(candidate, query)
+}
+
+/// List of choices narrowed down by a fuzzy query
+///
+/// * `items`: every choice, in their original ranking
+/// * `matches`: the choices matching the last query, best first
+/// * `state`: ListState over `matches`
+#[derive(Debug, Default)]
+pub struct Picker {
+    items: Vec<String>,
+    pub matches: Vec<Match>,
+    pub state: ListState,
+}
+
+impl Picker {
+    pub fn new(items: Vec<String>) -> Self {
+        let mut picker = Self {
+            items,
+            ..Default::default()
+        };
+        picker.filter("");
+        picker
+    }
+
+    pub fn filter(&mut self, query: &str) {
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                fuzzy_match(query, item).map(|(score, indices)| Match {
+                    index,
+                    score,
+                    indices,
+                })
+            })
+            .collect();
+        // stable, so equally good matches keep their original ranking
+        self.matches.sort_by_key(|m| -m.score);
+
+        let selected = if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.state.select(selected);
+    }
+
+    pub fn item(&self, m: &Match) -> &str {
+        &self.items[m.index]
+    }
+
+    /// index of the selected match among all the items
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|index| self.matches.get(index))
+            .map(|m| m.index)
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.state
+            .selected()
+            .and_then(|index| self.matches.get(index))
+            .map(|m| self.item(m))
+    }
+
+    pub fn select(&mut self, selection: Selection) {
+        use Selection::*;
+        let Some(last_index) = self.matches.len().checked_sub(1) else {
+            return;
+        };
+        let current = self.state.selected().unwrap_or_default();
+
+        match selection {
+            First => self.state.select_first(),
+            Last => self.state.select(Some(last_index)),
+            Next if current >= last_index => self.state.select_first(),
+            Next | NextNoWrap => self.state.select(Some(cmp::min(current + 1, last_index))),
+            Prev if current == 0 => self.state.select(Some(last_index)),
+            Prev | PrevNoWrap => self.state.select(Some(current.saturating_sub(1))),
+            Index(index) => self.state.select(index),
+            Noop => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Picker;
+    use crate::tui::tmux_list::Selection;
+
+    fn picker() -> Picker {
+        Picker::new(vec![
+            "/home/user/code/rusty-tmux-sessions".into(),
+            "/home/user/code/dotfiles".into(),
+            "/etc/tmux".into(),
+        ])
+    }
+
+    #[test]
+    fn empty_query_keeps_ranking() {
+        let picker = picker();
+
+        assert_eq!(3, picker.matches.len());
+        assert_eq!(
+            Some("/home/user/code/rusty-tmux-sessions"),
+            picker.selected()
+        );
+    }
+
+    #[test]
+    fn filters_and_reports_indices() {
+        let mut picker = picker();
+        picker.filter("dots");
+
+        assert_eq!(1, picker.matches.len());
+        assert_eq!(Some("/home/user/code/dotfiles"), picker.selected());
+        assert_eq!(Some(1), picker.selected_index());
+
+        let m = &picker.matches[0];
+        let matched: String = m
+            .indices
+            .iter()
+            .map(|i| picker.item(m).chars().nth(*i).unwrap())
+            .collect();
+        assert_eq!("dots", matched);
+    }
+
+    #[test]
+    fn no_matches() {
+        let mut picker = picker();
+        picker.filter("zzz");
+
+        assert_eq!(None, picker.selected());
+        picker.select(Selection::Next);
+        assert_eq!(None, picker.selected());
+    }
+
+    #[test]
+    fn selection_wraps() {
+        let mut picker = picker();
+
+        picker.select(Selection::Prev);
+        assert_eq!(Some("/etc/tmux"), picker.selected());
+        picker.select(Selection::Next);
+        assert_eq!(
+            Some("/home/user/code/rusty-tmux-sessions"),
+            picker.selected()
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use ratatui::text::Text;
+
+use crate::tmux::panes::{IdP, PaneService};
+
+use super::ansi;
+
+const REFRESH: Duration = Duration::from_millis(500);
+
+/// Content of a pane, captured in the background so input never waits on tmux
+///
+/// * `pane`: the pane being previewed
+/// * `content`: its last capture, without the trailing blank lines
+/// * `pending`: whether a capture is running
+#[derive(Debug)]
+pub struct Preview {
+    pane: Option<IdP>,
+    pub content: Text<'static>,
+    captured: Option<Instant>,
+    pending: bool,
+    tx: Sender<(IdP, Result<String>)>,
+    rx: Receiver<(IdP, Result<String>)>,
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            pane: None,
+            content: Text::default(),
+            captured: None,
+            pending: false,
+            tx,
+            rx,
+        }
+    }
+}
+
+impl Preview {
+    /// picks up finished captures and starts a new one when the pane changed
+    /// or the current content is getting old
+    pub fn update(&mut self, pane: Option<IdP>) {
+        while let Ok((id, content)) = self.rx.try_recv() {
+            self.pending = false;
+            if Some(id) == self.pane {
+                self.content = content.map(|c| to_text(&c)).unwrap_or_default();
+            }
+        }
+
+        if pane != self.pane {
+            self.pane = pane;
+            self.content = Text::default();
+            self.captured = None;
+        }
+        let Some(pane) = self.pane else {
+            return;
+        };
+        let stale = self.captured.is_none_or(|at| at.elapsed() >= REFRESH);
+        if stale && !self.pending {
+            self.capture(pane);
+        }
+    }
+
+    fn capture(&mut self, pane: IdP) {
+        self.pending = true;
+        self.captured = Some(Instant::now());
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let _ = tx.send((pane, PaneService::capture(&pane.into())));
+        });
+    }
+}
+
+fn to_text(content: &str) -> Text<'static> {
+    let mut text = ansi::to_text(content);
+    while text.lines.last().is_some_and(|line| line.width() == 0) {
+        text.lines.pop();
+    }
+    text
+}
@@ -0,0 +1,143 @@
+use std::{cmp::Reverse, fmt::Display};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::tmux::sessions::Session;
+
+/// Order of the session list, pinned sessions always come first
+#[derive(ValueEnum, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortStrategy {
+    /// most recently attached first, the current session last
+    #[default]
+    Recent,
+    Alphabetical,
+    /// oldest first
+    Created,
+    /// most windows first
+    Windows,
+    /// the order sessions got moved into
+    Manual,
+}
+
+impl Display for SortStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortStrategy::Recent => "recent",
+            SortStrategy::Alphabetical => "alphabetical",
+            SortStrategy::Created => "created",
+            SortStrategy::Windows => "windows",
+            SortStrategy::Manual => "manual",
+        })
+    }
+}
+
+impl SortStrategy {
+    pub fn next(self) -> Self {
+        use SortStrategy::*;
+        match self {
+            Recent => Alphabetical,
+            Alphabetical => Created,
+            Created => Windows,
+            Windows => Manual,
+            Manual => Recent,
+        }
+    }
+
+    pub fn sort(self, sessions: &mut [Session]) {
+        use SortStrategy::*;
+
+        match self {
+            Recent | Manual => {
+                sessions.sort_by_key(|s| s.last_attached);
+                sessions.reverse();
+                sessions.rotate_left(1);
+            }
+            Alphabetical => sessions.sort_by_key(|s| s.name.to_lowercase()),
+            Created => sessions.sort_by_key(|s| s.created_at),
+            Windows => sessions.sort_by_key(|s| Reverse(s.windows_count)),
+        }
+        match self {
+            // sessions that were never moved keep their recency
+            Manual => sessions.sort_by_key(|s| (s.pinned_at.is_none(), s.order.is_none(), s.order)),
+            // pinned sessions in the order they got pinned in
+            _ => sessions.sort_by_key(|s| (s.pinned_at.is_none(), s.pinned_at)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::SortStrategy::{self, *};
+    use crate::tmux::sessions::Session;
+
+    fn sessions() -> Vec<Session> {
+        [
+            "$1\x1fbeta\x1f0\x1f300\x1f1\x1f100\x1f\x1f\x1f1",
+            "$2\x1fAlpha\x1f1\x1f400\x1f3\x1f300\x1f\x1f\x1f",
+            "$3\x1fgamma\x1f0\x1f200\x1f2\x1f200\x1f\x1f\x1f0",
+            "$4\x1fdelta\x1f0\x1f100\x1f1\x1f400\x1f\x1f\x1f",
+        ]
+        .into_iter()
+        .map(|s| Session::from_str(s).unwrap())
+        .collect()
+    }
+
+    fn names(strategy: SortStrategy, mut sessions: Vec<Session>) -> Vec<String> {
+        strategy.sort(&mut sessions);
+        sessions.into_iter().map(|s| s.name).collect()
+    }
+
+    #[test]
+    fn strategies() {
+        assert_eq!(
+            vec!["beta", "gamma", "delta", "Alpha"],
+            names(Recent, sessions())
+        );
+        assert_eq!(
+            vec!["Alpha", "beta", "delta", "gamma"],
+            names(Alphabetical, sessions())
+        );
+        assert_eq!(
+            vec!["beta", "gamma", "Alpha", "delta"],
+            names(Created, sessions())
+        );
+        assert_eq!(
+            vec!["Alpha", "gamma", "beta", "delta"],
+            names(Windows, sessions())
+        );
+        assert_eq!(
+            vec!["gamma", "beta", "delta", "Alpha"],
+            names(Manual, sessions())
+        );
+    }
+
+    #[test]
+    fn pinned_first() {
+        let mut sessions = sessions();
+        sessions[3].pinned_at = Some(20);
+        sessions[1].pinned_at = Some(10);
+
+        assert_eq!(
+            vec!["Alpha", "delta", "beta", "gamma"],
+            names(Alphabetical, sessions.clone())
+        );
+        assert_eq!(
+            vec!["delta", "Alpha", "gamma", "beta"],
+            names(Manual, sessions)
+        );
+    }
+
+    #[test]
+    fn cycles_through_every_strategy() {
+        let mut strategy = SortStrategy::default();
+        for _ in 0..5 {
+            strategy = strategy.next();
+        }
+        assert_eq!(SortStrategy::default(), strategy);
+        assert_eq!(Alphabetical, Recent.next());
+    }
+}
@@ -1,4 +1,4 @@
-use std::ops::Index;
+use std::{cmp, ops::Index};
 
 use ratatui::widgets::ListState;
 
@@ -13,36 +13,68 @@ pub enum Selection {
     Noop,
 }
 
+/// Id and display name of a list item, along with the name's chars matched by
+/// a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListEntry<I> {
+    pub id: I,
+    pub name: String,
+    pub matches: Vec<usize>,
+}
+
+impl<I> From<(I, String)> for ListEntry<I> {
+    fn from((id, name): (I, String)) -> Self {
+        Self {
+            id,
+            name,
+            matches: vec![],
+        }
+    }
+}
+
+impl<I> ListEntry<I> {
+    pub fn with_matches(mut self, matches: Vec<usize>) -> Self {
+        self.matches = matches;
+        self
+    }
+}
+
 /// Wrapper for a stateful TUI list
 ///
-/// * `items`: Vector of Sessions or Windows
+/// * `items`: Vector of Sessions, Windows or Panes keyed by their id
 /// * `state`: ListState
 /// * `show_hidden`: Whether to show hidden items or not
+/// * `marks`: Items toggled one by one in visual mode
+/// * `anchor`: Item the visual range started from, the range ends at the selection
 #[derive(Debug)]
-pub struct StatefulList {
-    pub items: Vec<String>,
+pub struct StatefulList<I> {
+    pub items: Vec<ListEntry<I>>,
     pub state: ListState,
     show_hidden: bool,
+    marks: Vec<I>,
+    anchor: Option<I>,
 }
 
-impl Default for StatefulList {
+impl<I> Default for StatefulList<I> {
     fn default() -> Self {
         Self {
             items: Default::default(),
             state: ListState::default().with_selected(Some(0)),
             show_hidden: false,
+            marks: vec![],
+            anchor: None,
         }
     }
 }
 
-impl StatefulList {
-    pub fn with_items(items: Vec<String>) -> Self {
+impl<I: Copy> StatefulList<I> {
+    pub fn with_items(items: Vec<ListEntry<I>>) -> Self {
         let mut list = Self::default();
         list.items(items);
         list
     }
 
-    pub fn items(&mut self, items: Vec<String>) {
+    pub fn items(&mut self, items: Vec<ListEntry<I>>) {
         self.items = items;
     }
 
@@ -51,35 +83,210 @@ impl StatefulList {
         self.show_hidden = !self.show_hidden;
     }
 
-    pub fn get_active_item(&self) -> String {
+    pub fn shows_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    fn get_active_entry(&self) -> &ListEntry<I> {
         let active_idx = self
             .state
             .selected()
             .expect("there should always be a selection");
-        let index = self.items.index(active_idx);
-        index.clone()
+        self.items.index(active_idx)
+    }
+
+    /// name of the selected item, empty when the list is
+    pub fn get_active_item(&self) -> String {
+        self.state
+            .selected()
+            .and_then(|index| self.items.get(index))
+            .map(|item| item.name.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn get_active_id(&self) -> I {
+        self.get_active_entry().id
+    }
+
+    /// starts a visual range at the selected item
+    pub fn start_range(&mut self)
+    where
+        I: PartialEq,
+    {
+        self.marks.clear();
+        self.anchor = Some(self.get_active_id());
+    }
+
+    /// flips whether the selected item is marked
+    pub fn toggle_mark(&mut self)
+    where
+        I: PartialEq,
+    {
+        let id = self.get_active_id();
+        match self.marks.iter().position(|mark| *mark == id) {
+            Some(index) => {
+                self.marks.remove(index);
+            }
+            None => self.marks.push(id),
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+        self.anchor = None;
+    }
+
+    /// marked items are either in the range or toggled, but not both
+    pub fn is_marked(&self, id: &I) -> bool
+    where
+        I: PartialEq,
+    {
+        let in_range = match (self.anchor, self.state.selected()) {
+            (Some(anchor), Some(selected)) => {
+                let anchor = self.items.iter().position(|item| item.id == anchor);
+                let index = self.items.iter().position(|item| item.id == *id);
+                match (anchor, index) {
+                    (Some(anchor), Some(index)) => {
+                        cmp::min(anchor, selected) <= index && index <= cmp::max(anchor, selected)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+        in_range != self.marks.contains(id)
     }
 
-    /// selection function that handles 4 different cases
+    /// ids of the marked items, in list order
+    pub fn marked(&self) -> Vec<I>
+    where
+        I: PartialEq,
+    {
+        self.items
+            .iter()
+            .map(|item| item.id)
+            .filter(|id| self.is_marked(id))
+            .collect()
+    }
+
+    /// selects the item with the given id, or the first one if it's gone
+    pub fn select_id(&mut self, id: I)
+    where
+        I: PartialEq,
+    {
+        let index = self.items.iter().position(|item| item.id == id);
+        self.state.select(Some(index.unwrap_or_default()));
+    }
+
+    /// selects the item with the given id, or if it's gone the one above
+    /// where it was, at `index`
+    pub fn select_id_or_above(&mut self, id: I, index: usize)
+    where
+        I: PartialEq,
+    {
+        let last_index = self.items.len().saturating_sub(1);
+        let index = match self.items.iter().position(|item| item.id == id) {
+            Some(index) => index,
+            None => cmp::min(index.saturating_sub(1), last_index),
+        };
+        self.state.select(Some(index));
+    }
+
+    /// selection function that handles 4 different cases, the selection
+    /// always ends up on an item
     ///
     /// * `selection`: Selection
-    pub fn select(&mut self, selection: Selection) -> String {
+    pub fn select(&mut self, selection: Selection) -> Option<I> {
         use Selection::*;
-        let last_index = self.items.len() - 1;
-        let current = self.state.selected().expect("invalid selection");
-
-        match selection {
-            First => self.state.select_first(),
-            Last => self.state.select(Some(last_index)),
-            Next if current == last_index => self.state.select_first(),
-            Next => self.state.select_next(),
-            Prev if current == 0 => self.state.select(Some(last_index)),
-            Prev => self.state.select_previous(),
-            Index(index) => self.state.select(index),
-            NextNoWrap => self.state.select_next(),
-            PrevNoWrap => self.state.select_previous(),
-            Noop => (),
+        if self.items.is_empty() {
+            return None;
         }
-        self.get_active_item()
+        let last_index = self.items.len() - 1;
+        // the items may have changed since the last selection
+        let current = cmp::min(self.state.selected().unwrap_or_default(), last_index);
+
+        let index = match selection {
+            First => 0,
+            Last => last_index,
+            Next if current == last_index => 0,
+            Next | NextNoWrap => cmp::min(current + 1, last_index),
+            Prev if current == 0 => last_index,
+            Prev | PrevNoWrap => current.saturating_sub(1),
+            Index(index) => cmp::min(index.unwrap_or_default(), last_index),
+            Noop => current,
+        };
+        self.state.select(Some(index));
+        Some(self.get_active_id())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Selection, StatefulList};
+
+    fn list() -> StatefulList<usize> {
+        let items = (0..5).map(|id| (id, format!("item {id}")).into()).collect();
+        StatefulList::with_items(items)
+    }
+
+    #[test]
+    fn stays_within_the_items() {
+        let mut list = list();
+        list.select(Selection::Last);
+        assert_eq!(Some(4), list.select(Selection::NextNoWrap));
+
+        list.items.truncate(2);
+        assert_eq!(Some(1), list.select(Selection::Noop));
+        assert_eq!(Some(1), list.select(Selection::Index(Some(7))));
+        assert_eq!(Some(0), list.select(Selection::PrevNoWrap));
+        assert_eq!(Some(0), list.select(Selection::PrevNoWrap));
+    }
+
+    #[test]
+    fn marks_range_from_anchor() {
+        let mut list = list();
+        list.select(Selection::Index(Some(1)));
+        list.start_range();
+        list.select(Selection::Next);
+        list.select(Selection::Next);
+
+        assert_eq!(vec![1, 2, 3], list.marked());
+
+        list.select(Selection::First);
+        assert_eq!(vec![0, 1], list.marked());
+    }
+
+    #[test]
+    fn toggles_marks() {
+        let mut list = list();
+        list.toggle_mark();
+        list.select(Selection::Last);
+        list.toggle_mark();
+        assert_eq!(vec![0, 4], list.marked());
+
+        // toggling inside the range unmarks the item
+        list.start_range();
+        list.select(Selection::Prev);
+        list.toggle_mark();
+        assert_eq!(vec![4], list.marked());
+
+        list.clear_marks();
+        assert!(list.marked().is_empty());
+    }
+
+    #[test]
+    fn reselects_by_id() {
+        let mut list = list();
+        list.select_id_or_above(3, 1);
+        assert_eq!(Some(3), list.state.selected());
+
+        // the item above the removed one takes its place
+        list.items.retain(|item| item.id != 3);
+        list.select_id_or_above(3, 3);
+        assert_eq!(2, list.get_active_id());
+
+        list.items.truncate(1);
+        list.select_id_or_above(3, 3);
+        assert_eq!(0, list.get_active_id());
     }
 }
@@ -7,7 +7,10 @@ use ratatui::{
     },
     Terminal,
 };
-use std::io::{self, Stdout};
+use std::{
+    io::{self, Stdout},
+    panic,
+};
 
 use super::{app::App, event::EventHandler, view};
 
@@ -24,6 +27,14 @@ impl TUI {
     }
 
     pub fn init(&mut self) -> io::Result<()> {
+        // give the terminal back before a panic message gets printed
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = terminal::disable_raw_mode();
+            let _ = crossterm::execute!(io::stdout(), LeaveAlternateScreen);
+            hook(info);
+        }));
+
         terminal::enable_raw_mode()?;
         crossterm::execute!(io::stdout(), EnterAlternateScreen)?;
         self.terminal.hide_cursor()?;
@@ -1,36 +1,131 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use ratatui::{
-    layout::Rect,
+    layout::{Alignment, Constraint, Flex, Rect},
     style::{Style, Stylize},
     text::{Line, Span, Text},
-    widgets::{block::Title, Block, BorderType, List, Paragraph},
+    widgets::{block::Title, Block, Clear, List, ListItem, Paragraph, Row, Table},
     Frame,
 };
 use ratatui_macros::{horizontal, vertical};
 
 use crate::tui::mode::Section;
 
-use super::{app::App, mode::Mode};
+use super::{
+    app::App,
+    config::Theme,
+    mode::{Batch, Mode},
+    widgets::details::Details,
+};
 
 pub fn render(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
     let [body, footer_area] = vertical![*=1, ==3].areas(frame.area());
-    let [session_area, window_area] = horizontal![==50%, ==50%].areas(body);
+    let [lists, details_area] = match app.show_details {
+        true => vertical![*=1, ==5].areas(body),
+        false => [body, Rect::default()],
+    };
+    let [lists, preview_area] = vertical![*=1, ==50%].areas(lists);
+    let [session_area, window_area, pane_area] = horizontal![==35%, ==35%, ==30%].areas(lists);
 
     render_session_list(frame, session_area, app);
     render_window_list(frame, window_area, app);
+    render_pane_list(frame, pane_area, app);
+    render_preview(frame, preview_area, app);
     render_footer(frame, footer_area, app);
+
+    if app.show_details {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let details = Details {
+            session: app.selected_session(),
+            window: app.selected_window(),
+            pane: app.active_pane(),
+            clients: app.clients(),
+            now,
+            theme,
+        };
+        frame.render_widget(details, details_area);
+    }
+
+    let picker_title = match app.mode {
+        Mode::Pick(..) => Some(" Directories "),
+        Mode::Palette(..) => Some(" Jump to window "),
+        _ => None,
+    };
+    if let Some(title) = picker_title {
+        let [area] = vertical![==80%].flex(Flex::Center).areas(body);
+        let [area] = horizontal![==60%].flex(Flex::Center).areas(area);
+        render_picker(frame, area, app, title);
+    }
+    if let Mode::Help(mode) = &app.mode {
+        let [area] = vertical![==80%].flex(Flex::Center).areas(body);
+        let [area] = horizontal![==60%].flex(Flex::Center).areas(area);
+        render_help(frame, area, app, mode);
+    }
+}
+
+/// popup listing the keys available in the mode the help was opened from
+fn render_help(frame: &mut Frame, area: Rect, app: &App, mode: &Mode) {
+    let theme = app.theme;
+    let title = match mode {
+        Mode::Select(Section::Sessions) => " Help: sessions ",
+        Mode::Select(Section::Windows) => " Help: windows ",
+        Mode::Select(Section::Panes) => " Help: panes ",
+        Mode::Visual(_) => " Help: visual mode ",
+        Mode::Search(..) => " Help: search ",
+        Mode::Send(..) => " Help: send ",
+        Mode::Pick(..) => " Help: directory picker ",
+        _ => " Help: window palette ",
+    };
+    let block = Block::bordered()
+        .border_type(theme.border)
+        .border_style(Style::default().fg(theme.prompt))
+        .title(title.bold());
+
+    let help = app.keymap.help(mode);
+    let width = help
+        .iter()
+        .map(|(keys, _)| keys.len())
+        .max()
+        .unwrap_or_default();
+    let rows: Vec<Row> = help
+        .into_iter()
+        .map(|(keys, description)| {
+            Row::new(vec![
+                Span::from(keys).bold().fg(theme.prompt),
+                Span::from(description),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [Constraint::Length(width as u16), Constraint::Fill(1)],
+    )
+    .column_spacing(2)
+    .block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
 }
 
 fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
     use Mode::*;
     use Section::*;
 
     let active_item: Option<String> = match app.mode {
-        Select(Sessions) | Delete(Sessions) | Rename(Sessions, _) => {
+        Select(Sessions) | Delete(Sessions) | Rename(Sessions, _) | Send(Sessions, _) => {
             Some(app.session_list.get_active_item())
         }
-        Select(Windows) | Delete(Windows) | Rename(Windows, _) => {
+        Select(Windows) | Delete(Windows) | Rename(Windows, _) | Send(Windows, _) => {
             Some(app.window_list.get_active_item())
         }
+        Select(Panes) | Delete(Panes) | Rename(Panes, _) | Send(Panes, _) => {
+            Some(app.pane_list.get_active_item())
+        }
         _ => None,
     };
     let active_item = active_item.map(|name| Span::from(name).bold());
@@ -38,37 +133,121 @@ fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
     let title = match &app.mode {
         Select(Sessions) => vec![
             " Session: ".into(),
-            active_item.expect("should have a selected item").green(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.active),
             " ".into(),
         ],
         Select(Windows) => vec![
             " Window: ".into(),
-            active_item.expect("should have a selected item").green(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.active),
+            " ".into(),
+        ],
+        Select(Panes) => vec![
+            " Pane: ".into(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.active),
             " ".into(),
         ],
 
-        Create(Sessions, ..) => vec![" Enter new session name ".yellow()],
-        Create(Windows, ..) => vec![" Enter new window name ".yellow()],
+        Create(Sessions, ..) => match app.selected_template() {
+            Some(template) => vec![
+                " Enter new session name from template ".fg(theme.prompt),
+                Span::from(template.as_str()).bold().fg(theme.active),
+                " (tab to cycle) ".fg(theme.prompt),
+            ],
+            None => vec![" Enter new session name (tab for templates) ".fg(theme.prompt)],
+        },
+        Create(Windows, ..) => vec![" Enter new window name ".fg(theme.prompt)],
+        Pick(Sessions, _) => vec![" Filter directories for the new session ".fg(theme.prompt)],
+        Pick(Windows, _) => vec![" Filter directories for the new window ".fg(theme.prompt)],
+        Search(Sessions, _) => vec![" Search sessions (tab for windows) ".fg(theme.search)],
+        Search(Windows, _) => vec![" Search windows (tab for sessions) ".fg(theme.search)],
+        Palette(..) => vec![" Jump to any window by session:window ".fg(theme.prompt)],
+        Send(section, _) => match (section, app.marked_count(*section)) {
+            (Sessions, 0) => vec![
+                " Send a command to every window of ".fg(theme.prompt),
+                active_item
+                    .expect("should have a selected item")
+                    .fg(theme.active),
+                " ".into(),
+            ],
+            (Sessions, count) => {
+                vec![
+                    format!(" Send a command to every window of {count} marked sessions ")
+                        .fg(theme.prompt),
+                ]
+            }
+            (Windows, 0) => vec![
+                " Send a command to window ".fg(theme.prompt),
+                active_item
+                    .expect("should have a selected item")
+                    .fg(theme.active),
+                " ".into(),
+            ],
+            (_, 0) => vec![
+                " Send a command to pane ".fg(theme.prompt),
+                active_item
+                    .expect("should have a selected item")
+                    .fg(theme.active),
+                " ".into(),
+            ],
+            (_, count) => {
+                vec![format!(" Send a command to {count} marked windows ").fg(theme.prompt)]
+            }
+        },
+        Visual(section) | Confirm(section, _) => vec![
+            " Visual: ".into(),
+            format!("{} marked", app.marked_count(*section))
+                .bold()
+                .fg(theme.visual),
+            " ".into(),
+        ],
 
         Delete(Sessions) => vec![
             " Window: ".into(),
-            active_item.expect("should have a selected item").red(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.danger),
             " ".into(),
         ],
         Delete(Windows) => vec![
             " Window: ".into(),
-            active_item.expect("should have a selected item").red(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.danger),
+            " ".into(),
+        ],
+        Delete(Panes) => vec![
+            " Pane: ".into(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.danger),
             " ".into(),
         ],
 
         Rename(Sessions, _) => vec![
             " Enter new name for session ".into(),
-            active_item.expect("should have a selected item").magenta(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.rename),
             " ".into(),
         ],
         Rename(Windows, _) => vec![
             " Enter new name for window ".into(),
-            active_item.expect("should have a selected item").magenta(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.rename),
+            " ".into(),
+        ],
+        Rename(Panes, _) => vec![
+            " Enter new title for pane ".into(),
+            active_item
+                .expect("should have a selected item")
+                .fg(theme.rename),
             " ".into(),
         ],
         _ => vec!["".into()],
@@ -76,34 +255,155 @@ fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
     let title = Title::from(Line::from(title));
 
     let text = match &app.mode {
-        Select(_) => vec!["selecting".into()],
+        Select(_) | Visual(_) => vec![app.keymap.hints(&app.mode).into()],
+        Help(_) => vec![" Press any key to close the help ".into()],
 
-        Delete(Sessions) => vec![" Press y to delete session or any other key to cancel ".red()],
-        Delete(Windows) => vec![" Press y to delete window or any other key to cancel ".red()],
+        Delete(Sessions) => {
+            vec![" Press y to delete session or any other key to cancel ".fg(theme.danger)]
+        }
+        Delete(Windows) => {
+            vec![" Press y to delete window or any other key to cancel ".fg(theme.danger)]
+        }
+        Delete(Panes) => {
+            vec![" Press y to delete pane or any other key to cancel ".fg(theme.danger)]
+        }
 
-        Rename(_, input) | Create(_, input, _) => vec![input.content.as_str().into()],
+        Confirm(section, batch) => {
+            let count = app.marked_count(*section);
+            let noun = match (section, count) {
+                (Sessions, 1) => "session",
+                (Sessions, _) => "sessions",
+                (_, 1) => "window",
+                _ => "windows",
+            };
+            let verb = match batch {
+                Batch::Kill => "kill",
+                Batch::Hide => "hide or show",
+                Batch::Pin => "pin or unpin",
+            };
+            vec![
+                format!(" Press y to {verb} {count} {noun} or any other key to cancel ")
+                    .fg(theme.danger),
+            ]
+        }
+
+        Rename(_, input)
+        | Create(_, input, _)
+        | Pick(_, input)
+        | Search(_, input)
+        | Palette(_, input)
+        | Send(_, input) => {
+            vec![input.content.as_str().into()]
+        }
         _ => vec!["".into()],
     };
     let text = Text::from(Line::from(text));
 
-    let block = Block::bordered()
-        .border_type(BorderType::Thick)
-        .title(title);
+    let block = Block::bordered().border_type(theme.border).title(title);
     let block = match app.mode {
-        Delete(_) => block.border_style(Style::default().red()),
-        Create(..) => block.border_style(Style::default().green()),
+        Delete(_) | Confirm(..) => block.border_style(Style::default().fg(theme.danger)),
+        Visual(_) => block.border_style(Style::default().fg(theme.visual)),
+        Create(..) | Pick(..) | Palette(..) | Send(..) => {
+            block.border_style(Style::default().fg(theme.active))
+        }
+        Search(..) => block.border_style(Style::default().fg(theme.search)),
         _ => block,
     };
 
+    // what went wrong, or else what tmux is busy with
+    let status = match (app.notification(), app.pending()) {
+        (Some(message), _) => Some(format!(" {message} ").fg(theme.danger)),
+        (None, Some(label)) => Some(format!(" {} {label} ", spinner()).fg(theme.prompt)),
+        (None, None) => None,
+    };
+    let block = match status {
+        Some(status) => block.title(Title::from(status).alignment(Alignment::Right)),
+        None => block,
+    };
+
     frame.render_widget(Paragraph::new(text).block(block), area);
 }
 
+fn spinner() -> &'static str {
+    const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    FRAMES[(millis / 80) as usize % FRAMES.len()]
+}
+
+/// popup listing the directories matching the query, matched characters highlighted
+fn render_picker(frame: &mut Frame, area: Rect, app: &App, title: &str) {
+    let theme = app.theme;
+    let block = Block::bordered()
+        .border_type(theme.border)
+        .border_style(Style::default().fg(theme.active))
+        .title(title.bold());
+
+    let items: Vec<ListItem> = app
+        .picker
+        .matches
+        .iter()
+        .map(|m| ListItem::new(highlight(app.picker.item(m), &m.indices, &theme)))
+        .collect();
+    let list = List::new(items).highlight_symbol("> ").block(block);
+
+    let mut state = app.picker.state.clone();
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// highlights the chars of `text` at the given char indices
+fn highlight<'a>(text: &'a str, indices: &[usize], theme: &Theme) -> Line<'a> {
+    if indices.is_empty() {
+        return Line::from(text);
+    }
+    let spans: Vec<Span> = text
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match indices.contains(&i) {
+            true => Span::from(c.to_string()).bold().fg(theme.prompt),
+            false => Span::from(c.to_string()),
+        })
+        .collect();
+    Line::from(spans)
+}
+
 pub fn render_session_list(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let title = match app.session_list.shows_hidden() {
+        true => format!(" Sessions ({}, showing hidden) ", app.sort),
+        false => format!(" Sessions ({}) ", app.sort),
+    };
     let block = Block::bordered()
-        .border_type(BorderType::Thick)
-        .title(" Sessions ".bold());
+        .border_type(theme.border)
+        .title(title.bold());
 
-    let list: List = app.session_list.items.iter().map(|s| s as &str).collect();
+    let list: List = app
+        .session_list
+        .items
+        .iter()
+        .map(|s| {
+            let mut line = highlight(&s.name, &s.matches, &theme);
+            let session = app.session(&s.id);
+            if session.is_some_and(|s| s.pinned_at.is_some()) {
+                line.spans.insert(0, "* ".fg(theme.prompt).bold());
+            }
+            let line = match session.is_some_and(|s| s.is_hidden) {
+                true => {
+                    let mut line = line.dim();
+                    line.push_span(" (hidden)".italic());
+                    line
+                }
+                false => line,
+            };
+            match app.session_list.is_marked(&s.id) {
+                true => line.bg(theme.marked),
+                false => line,
+            }
+        })
+        .collect();
     let list = list.highlight_symbol("> ").block(block);
 
     let mut state = app.session_list.state.clone();
@@ -111,13 +411,60 @@ pub fn render_session_list(frame: &mut Frame, area: Rect, app: &App) {
 }
 
 pub fn render_window_list(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
     let block = Block::bordered()
-        .border_type(BorderType::Thick)
+        .border_type(theme.border)
         .title(" Windows ".bold());
 
-    let list: List = app.window_list.items.iter().map(|w| w as &str).collect();
+    let list: List = app
+        .window_list
+        .items
+        .iter()
+        .map(|w| {
+            let line = highlight(&w.name, &w.matches, &theme);
+            match app.window_list.is_marked(&w.id) {
+                true => line.bg(theme.marked),
+                false => line,
+            }
+        })
+        .collect();
     let list = list.highlight_symbol("> ").block(block);
 
     let mut state = app.window_list.state.clone();
     frame.render_stateful_widget(list, area, &mut state);
 }
+
+/// the active pane of the selected window, scrolled to its last lines
+fn render_preview(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let block = Block::bordered()
+        .border_type(theme.border)
+        .title(" Preview ".bold());
+
+    let content = app.preview.content.clone();
+    let height = area.height.saturating_sub(2) as usize;
+    let offset = content.lines.len().saturating_sub(height) as u16;
+
+    frame.render_widget(
+        Paragraph::new(content).block(block).scroll((offset, 0)),
+        area,
+    );
+}
+
+pub fn render_pane_list(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let block = Block::bordered()
+        .border_type(theme.border)
+        .title(" Panes ".bold());
+
+    let list: List = app
+        .pane_list
+        .items
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    let list = list.highlight_symbol("> ").block(block);
+
+    let mut state = app.pane_list.state.clone();
+    frame.render_stateful_widget(list, area, &mut state);
+}
@@ -0,0 +1,120 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget},
+};
+
+use crate::{
+    tmux::{panes::Pane, sessions::Session, windows::Window},
+    tui::config::Theme,
+};
+
+/// how long ago `then` was, in the largest unit that fits
+pub fn ago(then: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(then);
+    match seconds {
+        0..=59 => "just now".into(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+/// Details of the selected session and window, and of the window's active pane
+pub struct Details<'a> {
+    pub session: Option<&'a Session>,
+    pub window: Option<&'a Window>,
+    pub pane: Option<&'a Pane>,
+    pub clients: &'a [String],
+    pub now: u64,
+    pub theme: Theme,
+}
+
+impl<'a> Details<'a> {
+    fn session_line(&self, session: &'a Session) -> Line<'a> {
+        let attached = match self.clients {
+            [] if session.is_attached => "attached".into(),
+            [] => "detached".into(),
+            clients => format!("attached to {}", clients.join(", ")),
+        };
+        let last_attached = match session.last_attached {
+            Some(last) => ago(last, self.now),
+            None => "never".into(),
+        };
+        Line::from(vec![
+            " Session ".bold(),
+            Span::from(session.name.as_str()).fg(self.theme.active),
+            format!(
+                ": {attached}, created {}, last attached {last_attached}, {} windows",
+                ago(session.created_at, self.now),
+                session.windows_count
+            )
+            .into(),
+        ])
+    }
+
+    fn window_line(&self, window: &'a Window) -> Line<'a> {
+        let active = match window.is_active {
+            true => "active, ",
+            false => "",
+        };
+        Line::from(vec![
+            " Window ".bold(),
+            Span::from(window.name.as_str()).fg(self.theme.active),
+            format!(
+                ": {active}last activity {}, {} panes",
+                ago(window.last_active, self.now),
+                window.panes_count
+            )
+            .into(),
+        ])
+    }
+
+    fn pane_line(&self, pane: &'a Pane) -> Line<'a> {
+        Line::from(vec![
+            " Active pane ".bold(),
+            Span::from(pane.current_command.as_str()).fg(self.theme.active),
+            " in ".into(),
+            Span::from(pane.current_path.as_str()).italic(),
+        ])
+    }
+}
+
+impl Widget for Details<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .border_type(self.theme.border)
+            .title(" Details ".bold());
+
+        let lines: Vec<Line> = [
+            self.session.map(|s| self.session_line(s)),
+            self.window.map(|w| self.window_line(w)),
+            self.pane.map(|p| self.pane_line(p)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ago;
+
+    #[test]
+    fn humanizes_times() {
+        let now = 1_000_000;
+
+        assert_eq!("just now", ago(now - 59, now));
+        assert_eq!("1m ago", ago(now - 60, now));
+        assert_eq!("59m ago", ago(now - 3599, now));
+        assert_eq!("2h ago", ago(now - 7200, now));
+        assert_eq!("3d ago", ago(now - 3 * 86400 - 5, now));
+        // clocks can disagree a little
+        assert_eq!("just now", ago(now + 5, now));
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use anyhow::Result;
+
+use crate::tmux::{
+    panes::Pane,
+    sessions::{IdS, Session},
+    windows::{IdW, Window},
+};
+
+use super::event::Events;
+
+pub type JobId = usize;
+
+type Job = Box<dyn FnOnce(JobId) -> Events + Send>;
+
+/// What a job read from tmux, or the directories to pick from, for the app to
+/// take over
+#[derive(Clone, Debug)]
+pub enum Loaded {
+    Sessions(Vec<Session>),
+    Session(Session),
+    Windows(IdS, Vec<Window>),
+    /// the windows of a session, one of which just got created
+    WindowCreated(IdS, IdW, Vec<Window>),
+    ServerWindows(Vec<Window>),
+    Panes(IdW, Vec<Pane>),
+    Pane(Pane),
+    Clients(IdS, Vec<String>),
+    Dirs(Vec<String>),
+}
+
+/// Runs the commands talking to tmux on a thread of its own, so a slow tmux
+/// server doesn't freeze the UI
+///
+/// Jobs run one after another in the order they came in, so a read queued
+/// after a change sees it. Changes report their result as an
+/// `Events::JobDone`, reads send what they loaded as an `Events::Loaded`.
+pub struct Worker {
+    tx: Sender<(JobId, Job)>,
+    next: JobId,
+}
+
+impl Default for Worker {
+    /// a worker whose results go nowhere
+    fn default() -> Self {
+        Self::new(mpsc::channel().0)
+    }
+}
+
+impl Worker {
+    pub fn new(events: Sender<Events>) -> Self {
+        let (tx, rx) = mpsc::channel::<(JobId, Job)>();
+
+        thread::spawn(move || {
+            for (id, job) in rx {
+                let _ = events.send(job(id));
+            }
+        });
+
+        Self { tx, next: 0 }
+    }
+
+    /// queues the job, its result comes with the returned id
+    pub fn run<F>(&mut self, job: F) -> JobId
+    where
+        F: FnOnce() -> Result<()> + Send + 'static,
+    {
+        self.queue(Box::new(move |id| {
+            Events::JobDone(id, job().map_err(|e| format!("{e:#}")))
+        }))
+    }
+
+    /// queues a job reading from tmux, what it loaded comes with the returned id
+    pub fn load<F>(&mut self, job: F) -> JobId
+    where
+        F: FnOnce() -> Result<Loaded> + Send + 'static,
+    {
+        self.queue(Box::new(move |id| {
+            Events::Loaded(id, job().map_err(|e| format!("{e:#}")))
+        }))
+    }
+
+    fn queue(&mut self, job: Job) -> JobId {
+        let id = self.next;
+        self.next += 1;
+        self.tx
+            .send((id, job))
+            .expect("worker thread should be running");
+        id
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc;
+
+    use anyhow::anyhow;
+
+    use super::{Loaded, Worker};
+    use crate::tui::event::Events;
+
+    #[test]
+    fn runs_jobs_in_order() {
+        let (tx, rx) = mpsc::channel();
+        let mut worker = Worker::new(tx);
+
+        let write = worker.run(|| Err(anyhow!("no server")));
+        let read = worker.load(|| Ok(Loaded::Sessions(vec![])));
+
+        match rx.recv().unwrap() {
+            Events::JobDone(id, Err(e)) => assert_eq!((write, "no server"), (id, e.as_str())),
+            event => panic!("unexpected {event:?}"),
+        }
+        match rx.recv().unwrap() {
+            Events::Loaded(id, Ok(Loaded::Sessions(sessions))) => {
+                assert_eq!(read, id);
+                assert!(sessions.is_empty());
+            }
+            event => panic!("unexpected {event:?}"),
+        }
+    }
+}